@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mk_core::{eframe::egui, Highlighter};
+
+fuzz_target!(|s: &str| {
+    let job = Highlighter::default().highlight(&egui::Style::default(), s, Default::default());
+    assert_eq!(job.text, s);
+});
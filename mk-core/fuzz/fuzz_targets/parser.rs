@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mk_core::parser::{Parser, ParserOptions};
+
+fuzz_target!(|s: &str| {
+    for _ in Parser::new(s) {}
+    for _ in Parser::with_options(s, ParserOptions { math: true, ..Default::default() }) {}
+    mk_core::parser::headings(s, Default::default());
+    mk_core::math::parse(s);
+});
@@ -0,0 +1,25 @@
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Line<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str)
+}
+
+pub fn lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new = new.split_inclusive('\n').collect::<Vec<_>>();
+
+    let mut lines = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            lines.extend(old[old_range].iter().copied().map(Line::Same));
+            continue;
+        }
+        lines.extend(old[old_range].iter().copied().map(Line::Removed));
+        lines.extend(new[new_range].iter().copied().map(Line::Added));
+    }
+    lines
+}
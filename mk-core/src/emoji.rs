@@ -0,0 +1,22 @@
+use crate::fuzzy::fuzzy_match;
+
+/// The emoji a GitHub shortcode like `tada` names, without its colons.
+pub fn from_shortcode(shortcode: &str) -> Option<&'static str> {
+    emojis::get_by_shortcode(shortcode).map(emojis::Emoji::as_str)
+}
+
+/// Shortcodes matching `query` with their emoji, shortcodes starting with
+/// the query first. An empty query lists every emoji in Unicode order.
+pub fn search(query: &str) -> Vec<(&'static str, &'static str)> {
+    let mut found = emojis::iter()
+        .flat_map(|emoji| emoji.shortcodes().map(move |shortcode| (shortcode, emoji.as_str())))
+        .filter_map(|(shortcode, emoji)| {
+            let found = fuzzy_match(query, shortcode)?;
+            Some((!shortcode.starts_with(query), -found.score, shortcode, emoji))
+        })
+        .collect::<Vec<_>>();
+    if !query.is_empty() {
+        found.sort_by_key(|&(prefix, score, shortcode, _)| (prefix, score, shortcode.len()));
+    }
+    found.into_iter().map(|(_, _, shortcode, emoji)| (shortcode, emoji)).collect()
+}
@@ -0,0 +1,183 @@
+/// The syntax of a front matter block, picked by its fence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Between `---` lines, closed by `---` or `...`.
+    Yaml,
+    /// Between `+++` lines.
+    Toml
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value<'a> {
+    Text(&'a str),
+    List(Vec<&'a str>)
+}
+
+/// Metadata at the very start of a note, like
+///
+/// ```text
+/// ---
+/// title: Groceries
+/// tags: [home, weekly]
+/// ---
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrontMatter<'a> {
+    pub format: Format,
+    /// Top level fields in the order they appear. Nested tables and
+    /// mappings are skipped.
+    pub fields: Vec<(&'a str, Value<'a>)>,
+    /// Length in bytes of the whole block, including the closing fence.
+    pub len: usize
+}
+
+impl<'a> FrontMatter<'a> {
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    fn text(&self, key: &str) -> Option<&'a str> {
+        match self.get(key)? {
+            Value::Text(text) if !text.is_empty() => Some(text),
+            _ => None
+        }
+    }
+
+    pub fn title(&self) -> Option<&'a str> {
+        self.text("title")
+    }
+
+    pub fn date(&self) -> Option<&'a str> {
+        self.text("date")
+    }
+
+    /// `tags` or `keywords`, either as a list or separated by commas or
+    /// spaces.
+    pub fn tags(&self) -> Vec<&'a str> {
+        match self.get("tags").or_else(|| self.get("keywords")) {
+            Some(Value::List(tags)) => tags.clone(),
+            Some(Value::Text(text)) if text.contains(',') => {
+                text.split(',').map(str::trim).filter(|tag| !tag.is_empty()).collect()
+            }
+            Some(Value::Text(text)) => text.split_whitespace().collect(),
+            None => Vec::new()
+        }
+    }
+
+    /// Fields other than the title, date and tags.
+    pub fn others(&self) -> impl Iterator<Item = &(&'a str, Value<'a>)> {
+        self.fields.iter().filter(|(name, _)| {
+            !["title", "date", "tags", "keywords"].iter().any(|known| name.eq_ignore_ascii_case(known))
+        })
+    }
+}
+
+/// Reads the front matter at the start of `s`. Blocks with lines that are
+/// not fields are taken to be ordinary Markdown, so a note that merely
+/// starts with a separator keeps rendering as one.
+pub fn parse(s: &str) -> Option<FrontMatter<'_>> {
+    let mut lines = s.split_inclusive('\n');
+    let format = match lines.next()?.trim_end() {
+        "---" => Format::Yaml,
+        "+++" => Format::Toml,
+        _ => return None
+    };
+
+    let mut len = s.find('\n')? + 1;
+    let mut fields = Vec::new();
+    // A list still being filled by the lines that follow its key.
+    let mut open_list = false;
+    let mut in_table = false;
+    for line in lines {
+        len += line.len();
+        let line = line.trim_end();
+        let closed = match format {
+            Format::Yaml => line == "---" || line == "...",
+            Format::Toml => line == "+++"
+        };
+        if closed {
+            fields.retain(|(_, value)| *value != Value::List(Vec::new()));
+            return Some(FrontMatter { format, fields, len });
+        }
+
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        match format {
+            Format::Yaml if line.starts_with([' ', '\t', '-']) => {
+                let item = trimmed
+                    .strip_prefix('-')
+                    .filter(|item| open_list && (item.is_empty() || item.starts_with(' ')));
+                match (item, fields.last_mut()) {
+                    (Some(item), Some((_, Value::List(items)))) => items.push(unquote(item.trim())),
+                    // Nested mappings and block scalars.
+                    _ => open_list = false
+                }
+            }
+            Format::Yaml => {
+                let (key, value) = line.split_once(':')?;
+                if !value.is_empty() && !value.starts_with([' ', '\t']) {
+                    return None;
+                }
+                let value = value.trim();
+                open_list = value.is_empty();
+                fields.push((unquote(key.trim()), yaml_value(value)));
+            }
+            Format::Toml if open_list => {
+                if let Some((_, Value::List(items))) = fields.last_mut() {
+                    let (rest, end) = match trimmed.split_once(']') {
+                        Some((rest, _)) => (rest, true),
+                        None => (trimmed, false)
+                    };
+                    items.extend(list_items(rest));
+                    open_list = !end;
+                }
+            }
+            Format::Toml if trimmed.starts_with('[') => in_table = true,
+            Format::Toml if in_table => {}
+            Format::Toml => {
+                let (key, value) = line.split_once('=')?;
+                let value = value.trim();
+                let value = match value.strip_prefix('[') {
+                    Some(list) => match list.strip_suffix(']') {
+                        Some(list) => Value::List(list_items(list).collect()),
+                        None => {
+                            open_list = true;
+                            Value::List(list_items(list).collect())
+                        }
+                    },
+                    None => Value::Text(unquote(value))
+                };
+                fields.push((unquote(key.trim()), value));
+            }
+        }
+    }
+    None
+}
+
+fn yaml_value(value: &str) -> Value<'_> {
+    match value.strip_prefix('[').and_then(|list| list.strip_suffix(']')) {
+        Some(list) => Value::List(list_items(list).collect()),
+        // Block scalars (`|` and `>`) span indented lines, which are skipped.
+        None if value.is_empty() || value.starts_with(['|', '>']) => Value::List(Vec::new()),
+        None => Value::Text(unquote(value))
+    }
+}
+
+fn list_items(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(|item| unquote(item.trim()))
+        .filter(|item| !item.is_empty())
+}
+
+fn unquote(text: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&quote| text.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(text)
+}
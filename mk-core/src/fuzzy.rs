@@ -0,0 +1,88 @@
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Match {
+    pub score: i64,
+    /// Char indices of `candidate` that matched the pattern.
+    pub positions: Vec<usize>
+}
+
+const MATCHED: i64 = 16;
+const CONSECUTIVE: i64 = 24;
+const WORD_START: i64 = 20;
+const GAP: i64 = 1;
+
+/// Matches `pattern` as a case-insensitive subsequence of `candidate`.
+/// Whitespace in the pattern is ignored, an empty pattern matches anything.
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<Match> {
+    let pattern = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let chars = candidate.chars().collect::<Vec<_>>();
+    // Lowercasing turns some chars into several, like `İ`, so each lowercase
+    // char keeps the index of the candidate char it came from.
+    let (lower, owners): (Vec<char>, Vec<usize>) = chars
+        .iter()
+        .enumerate()
+        .flat_map(|(i, c)| c.to_lowercase().map(move |lower| (lower, i)))
+        .unzip();
+
+    if pattern.is_empty() {
+        return Some(Match::default());
+    }
+
+    // Find the earliest end of a match going forwards, then walk back from
+    // it to find the latest start, which gives the tightest window.
+    let mut end = 0;
+    let mut p = 0;
+    for (i, c) in lower.iter().enumerate() {
+        if *c == pattern[p] {
+            p += 1;
+            if p == pattern.len() {
+                end = i;
+                break;
+            }
+        }
+    }
+    if p < pattern.len() {
+        return None;
+    }
+
+    let mut positions = vec![0; pattern.len()];
+    let mut p = pattern.len();
+    for i in (0..=end).rev() {
+        if lower[i] == pattern[p - 1] {
+            p -= 1;
+            positions[p] = i;
+            if p == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut score = 0;
+    for (n, &i) in positions.iter().enumerate() {
+        score += MATCHED;
+        if n > 0 && positions[n - 1] + 1 == i {
+            score += CONSECUTIVE;
+        } else if n > 0 {
+            score -= GAP * (i - positions[n - 1] - 1) as i64;
+        }
+        let first = i == 0 || owners[i - 1] != owners[i];
+        if first && is_word_start(&chars, owners[i]) {
+            score += WORD_START;
+        }
+    }
+    score -= GAP * positions[0] as i64;
+
+    let mut positions = positions.into_iter().map(|i| owners[i]).collect::<Vec<_>>();
+    positions.dedup();
+    Some(Match { score, positions })
+}
+
+fn is_word_start(chars: &[char], i: usize) -> bool {
+    let Some(previous) = i.checked_sub(1).map(|i| chars[i]) else {
+        return true;
+    };
+    !previous.is_alphanumeric() || (previous.is_lowercase() && chars[i].is_uppercase())
+}
@@ -1,10 +1,11 @@
 use eframe::egui::{self};
-use super::parser;
+use super::{front_matter, parser};
 
 #[derive(Default)]
 pub struct Highlighter {
     style: egui::Style,
     code: String,
+    options: parser::ParserOptions,
     output: egui::text::LayoutJob
 }
 
@@ -12,12 +13,14 @@ impl Highlighter {
     pub fn highlight(
         &mut self, 
         egui_style: &egui::Style,
-        code: &str
+        code: &str,
+        options: parser::ParserOptions
     ) -> egui::text::LayoutJob {
-        if (&self.style, self.code.as_str()) != (egui_style, code) {
+        if (&self.style, self.code.as_str(), self.options) != (egui_style, code, options) {
             self.style = egui_style.clone();
             code.clone_into(&mut self.code);
-            self.output = highlight_mark(egui_style, code);
+            self.options = options;
+            self.output = highlight_mark(egui_style, code, options);
         }
         self.output.clone()
     }
@@ -25,11 +28,29 @@ impl Highlighter {
 
 pub fn highlight_mark(
     egui_style: &egui::Style, 
-    mut text: &str
+    mut text: &str,
+    options: parser::ParserOptions
 ) -> egui::text::LayoutJob {
+    let marko = options.dialect == parser::Dialect::Marko;
+    let source = text;
+    let toggles = |text: &str, on: bool| {
+        let c = text.chars().next().unwrap_or_default();
+        let (open, close) = parser::flanking(source, source.len() - text.len(), c, options.dialect);
+        if on { close } else { open }
+    };
     let mut job = egui::text::LayoutJob::default();
     let mut style = parser::Style::default();
     let mut start_of_line = true;
+    let mut autolinks = parser::Autolinks::default();
+
+    if let Some(front) = front_matter::parse(text) {
+        let format = format_from_style(egui_style, &parser::Style {
+            code: true,
+            ..Default::default()
+        });
+        job.append(&text[..front.len], 0.0, format);
+        text = &text[front.len..];
+    }
 
     while !text.is_empty() {
         if start_of_line && text.starts_with("```") {
@@ -50,6 +71,18 @@ pub fn highlight_mark(
             continue;
         }
 
+        let math_span = options.math.then(|| parser::math_span(text, options.dialect)).flatten();
+        if let Some((len, ..)) = math_span {
+            let math = parser::Style {
+                code: true,
+                ..style
+            };
+            job.append(&text[..len], 0.0, format_from_style(egui_style, &math));
+            text = &text[len..];
+            start_of_line = false;
+            continue;
+        }
+
         if text.starts_with('`') {
             style.code = true;
             let end = text[1..]
@@ -65,10 +98,18 @@ pub fn highlight_mark(
             continue;
         }
 
+        let autolink = autolinks.next(source, source.len() - text.len());
+        if let Some(link) = autolink.as_ref().filter(|link| link.start == 0) {
+            job.append(&text[..link.end], 0.0, link_format(egui_style, &style));
+            text = &text[link.end..];
+            start_of_line = false;
+            continue;
+        }
+
         let mut skip;
 
-        if text.starts_with('\\') && text.len() >= 2 {
-            skip = 2;
+        if let Some(escaped) = text.strip_prefix('\\').and_then(|rest| rest.chars().next()) {
+            skip = 1 + escaped.len_utf8();
         } else if start_of_line && text.starts_with(' ') {
             skip = 1;
         } else if start_of_line && text.starts_with("# ") {
@@ -82,7 +123,7 @@ pub fn highlight_mark(
             skip = 2;
         } else if start_of_line && text.starts_with("- ") {
             skip = 2;
-        } else if text.starts_with('*') {
+        } else if text.starts_with('*') && toggles(text, style.strong) {
             skip = 1;
             if style.strong {
                 job.append(
@@ -94,7 +135,7 @@ pub fn highlight_mark(
                 skip = 0;
             }
             style.strong ^= true;
-        } else if text.starts_with('$') {
+        } else if marko && !options.math && text.starts_with('$') && toggles(text, style.small) {
             skip = 1;
             if style.small {
                 job.append(
@@ -106,7 +147,29 @@ pub fn highlight_mark(
                 skip = 0;
             }
             style.small ^= true;
-        } else if text.starts_with('^') {
+        } else if text.starts_with("==") && toggles(text, style.highlight) {
+            skip = 2;
+            if style.highlight {
+                job.append(
+                    &text[..skip],
+                    0.0,
+                    format_from_style(egui_style, &style)
+                );
+                text = &text[skip..];
+                skip = 0;
+            }
+            style.highlight ^= true;
+        } else if let Some((len, tagged)) = parser::inline_tag(text, style) {
+            // Tags are shown in the style of the text before them.
+            job.append(
+                &text[..len],
+                0.0,
+                format_from_style(egui_style, &style)
+            );
+            text = &text[len..];
+            skip = 0;
+            style = tagged;
+        } else if marko && text.starts_with('^') && toggles(text, style.raised) {
             skip = 1;
             if style.raised {
                 job.append(
@@ -122,12 +185,17 @@ pub fn highlight_mark(
             skip = 0;
         }
 
-        let line_end = text[skip..]
-            .find('\n')
-            .map_or_else(|| text.len(), |i| (skip + i + 1));
-        let end = text[skip..]
-            .find(&['*', '`', '~', '_', '/', '$', '^', '\\', '<', '['][..])
+        let mut end = text[skip..]
+            .find(&['*', '`', '~', '_', '/', '$', '^', '=', '\\', '<', '['][..])
             .map_or_else(|| text.len(), |i| (skip + i).max(1));
+        if let Some(link) = autolink.filter(|link| link.start >= skip) {
+            end = end.min(link.start);
+        }
+        // Only a line break before `end` matters, the rest of a long line
+        // is not searched.
+        let line_end = text[skip..end]
+            .find('\n')
+            .map_or_else(|| text.len(), |i| skip + i + 1);
 
         if line_end <= end {
             job.append(
@@ -152,13 +220,31 @@ pub fn highlight_mark(
     job
 }
 
+fn link_format(
+    egui_style: &egui::Style,
+    mark_style: &parser::Style
+) -> egui::text::TextFormat {
+    let color = egui_style.visuals.hyperlink_color;
+    egui::text::TextFormat {
+        color,
+        underline: egui::Stroke::new(1.0, color),
+        ..format_from_style(egui_style, mark_style)
+    }
+}
+
+/// Behind `==marked==` text, translucent so it works on light and dark
+/// backgrounds.
+pub const HIGHLIGHT: egui::Color32 = egui::Color32::from_rgba_premultiplied(96, 80, 0, 96);
+
 fn format_from_style(
     egui_style: &egui::Style,
     mark_style: &parser::Style
 ) -> egui::text::TextFormat {
     use egui::{Align, Color32, Stroke, TextStyle};
 
-    let color = if mark_style.strong || mark_style.heading || mark_style.subheading {
+    let color = if let Some([r, g, b]) = mark_style.color {
+        Color32::from_rgb(r, g, b)
+    } else if mark_style.strong || mark_style.heading || mark_style.subheading {
         egui_style.visuals.strong_text_color()
     } else if mark_style.quoted {
         egui_style.visuals.weak_text_color()
@@ -166,16 +252,18 @@ fn format_from_style(
         egui_style.visuals.text_color()
     };
 
-    let text_style = if mark_style.code {
+    let text_style = if mark_style.code || mark_style.keyboard {
         TextStyle::Monospace
-    } else if mark_style.small | mark_style.raised {
+    } else if mark_style.small | mark_style.raised | mark_style.lowered {
         TextStyle::Small
     } else {
         TextStyle::Body
     };
 
-    let background = if mark_style.code {
+    let background = if mark_style.code || mark_style.keyboard {
         egui_style.visuals.code_bg_color
+    } else if mark_style.highlight {
+        HIGHLIGHT
     } else {
         Color32::TRANSPARENT
     };
@@ -1,5 +1,14 @@
 pub mod parser;
+pub mod front_matter;
+pub mod math;
+pub mod diff;
+pub mod emoji;
+pub mod fuzzy;
+pub mod recovery;
+pub mod search;
+pub mod watcher;
+pub mod workspace;
 mod highlighter;
 
-pub use highlighter::Highlighter;
+pub use highlighter::{Highlighter, HIGHLIGHT};
 pub use eframe;
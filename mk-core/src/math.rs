@@ -0,0 +1,216 @@
+/// Groups and command arguments nested deeper than this read `{` and `\`
+/// as plain symbols, so no input can overflow the stack.
+const MAX_DEPTH: usize = 32;
+
+/// A TeX subset: enough for fractions, roots, scripts, Greek letters and
+/// common operators.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node<'a> {
+    /// A letter, digit or punctuation. Letters are set in italics.
+    Symbol(char),
+    /// A binary operator or relation, spaced out on both sides.
+    Operator(char),
+    /// `\sum`, `\int` and friends, set larger than the text around them.
+    Large(char),
+    /// Upright text, from function names like `\sin` and `\text{..}`.
+    Text(&'a str),
+    /// Horizontal space in ems, negative for `\!`.
+    Space(f32),
+    Group(Vec<Node<'a>>),
+    Fraction(Vec<Node<'a>>, Vec<Node<'a>>),
+    Root(Vec<Node<'a>>),
+    Scripts {
+        base: Box<Node<'a>>,
+        sub: Option<Vec<Node<'a>>>,
+        sup: Option<Vec<Node<'a>>>
+    }
+}
+
+pub fn parse(tex: &str) -> Vec<Node<'_>> {
+    Reader { s: tex, depth: 0 }.list(false)
+}
+
+struct Reader<'a> {
+    s: &'a str,
+    depth: usize
+}
+
+impl<'a> Reader<'a> {
+    /// Reads nodes up to the end, or the `}` that closes a group.
+    fn list(&mut self, in_group: bool) -> Vec<Node<'a>> {
+        let mut nodes = Vec::new();
+        loop {
+            self.s = self.s.trim_start();
+            let superscript = match self.s.chars().next() {
+                None => break,
+                Some('}') if in_group => {
+                    self.s = &self.s[1..];
+                    break;
+                }
+                Some('^') => true,
+                Some('_') => false,
+                Some(_) => {
+                    nodes.extend(self.atom());
+                    continue;
+                }
+            };
+
+            self.s = &self.s[1..];
+            let script = Some(self.argument());
+            // `x_i^2` puts both scripts on the same base. A second script of
+            // the same kind starts over on an empty base rather than nesting,
+            // which a long `x^2^2^2..` would do without bound.
+            let (base, mut sub, mut sup) = match nodes.pop() {
+                Some(Node::Scripts { base, sub, sup }) => {
+                    let taken = if superscript { sup.is_some() } else { sub.is_some() };
+                    if taken {
+                        nodes.push(Node::Scripts { base, sub, sup });
+                        (Box::new(Node::Group(Vec::new())), None, None)
+                    } else {
+                        (base, sub, sup)
+                    }
+                }
+                last => (Box::new(last.unwrap_or(Node::Group(Vec::new()))), None, None)
+            };
+            if superscript {
+                sup = script;
+            } else {
+                sub = script;
+            }
+            nodes.push(Node::Scripts { base, sub, sup });
+        }
+        nodes
+    }
+
+    /// A group, a command or a single character.
+    fn atom(&mut self) -> Option<Node<'a>> {
+        let start = self.s;
+        let c = self.s.chars().next()?;
+        self.s = &self.s[c.len_utf8()..];
+        Some(match c {
+            '{' if self.depth < MAX_DEPTH => {
+                self.depth += 1;
+                let nodes = self.list(true);
+                self.depth -= 1;
+                Node::Group(nodes)
+            }
+            '\\' if self.depth < MAX_DEPTH => self.command(start),
+            '-' => Node::Operator('−'),
+            '*' => Node::Operator('∗'),
+            '+' | '=' | '<' | '>' => Node::Operator(c),
+            c => Node::Symbol(c)
+        })
+    }
+
+    /// What a script or command applies to: a group or a single atom.
+    fn argument(&mut self) -> Vec<Node<'a>> {
+        self.s = self.s.trim_start();
+        if self.s.starts_with('}') {
+            return Vec::new();
+        }
+        self.depth += 1;
+        let atom = self.atom();
+        self.depth -= 1;
+        match atom {
+            Some(Node::Group(nodes)) => nodes,
+            Some(node) => vec![node],
+            None => Vec::new()
+        }
+    }
+
+    /// A command, with `start` still holding its backslash.
+    fn command(&mut self, start: &'a str) -> Node<'a> {
+        let len = self.s.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(self.s.len());
+        if len == 0 {
+            let Some(c) = self.s.chars().next() else {
+                return Node::Symbol('\\');
+            };
+            self.s = &self.s[c.len_utf8()..];
+            return match c {
+                ',' => Node::Space(0.17),
+                ':' | '>' => Node::Space(0.22),
+                ';' => Node::Space(0.28),
+                '!' => Node::Space(-0.17),
+                ' ' => Node::Space(0.33),
+                // Line breaks are not laid out, keep a gap instead.
+                '\\' => Node::Space(1.0),
+                c => Node::Symbol(c)
+            };
+        }
+
+        let name = &self.s[..len];
+        self.s = &self.s[len..];
+        match name {
+            "frac" | "dfrac" | "tfrac" => Node::Fraction(self.argument(), self.argument()),
+            "sqrt" => Node::Root(self.argument()),
+            "text" | "textrm" | "mathrm" | "operatorname" => {
+                let text = self.s.trim_start();
+                match text.strip_prefix('{').and_then(|text| Some(&text[..text.find('}')?])) {
+                    Some(inner) => {
+                        self.s = &text[inner.len() + 2..];
+                        Node::Text(inner)
+                    }
+                    None => Node::Group(self.argument())
+                }
+            }
+            // Delimiters are not stretched, so these only show what follows.
+            "left" | "right" => {
+                self.s = self.s.strip_prefix('.').unwrap_or(self.s);
+                Node::Group(Vec::new())
+            }
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => Node::Group(Vec::new()),
+            "quad" => Node::Space(1.0),
+            "qquad" => Node::Space(2.0),
+            _ if FUNCTIONS.contains(&name) => Node::Text(name),
+            _ => symbol(name).unwrap_or(Node::Text(&start[..len + 1]))
+        }
+    }
+}
+
+/// Set upright, like `\sin x`.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan",
+    "sinh", "cosh", "tanh", "log", "ln", "lg", "exp", "lim", "max", "min",
+    "sup", "inf", "det", "gcd", "deg", "dim", "ker"
+];
+
+/// Greek letters and other symbols set like variables.
+const SYMBOLS: &[(&str, char)] = &[
+    ("alpha", 'α'), ("beta", 'β'), ("gamma", 'γ'), ("delta", 'δ'), ("epsilon", 'ε'),
+    ("varepsilon", 'ε'), ("zeta", 'ζ'), ("eta", 'η'), ("theta", 'θ'), ("vartheta", 'ϑ'),
+    ("iota", 'ι'), ("kappa", 'κ'), ("lambda", 'λ'), ("mu", 'μ'), ("nu", 'ν'),
+    ("xi", 'ξ'), ("pi", 'π'), ("varpi", 'ϖ'), ("rho", 'ρ'), ("sigma", 'σ'),
+    ("tau", 'τ'), ("upsilon", 'υ'), ("phi", 'φ'), ("varphi", 'φ'), ("chi", 'χ'),
+    ("psi", 'ψ'), ("omega", 'ω'), ("Gamma", 'Γ'), ("Delta", 'Δ'), ("Theta", 'Θ'),
+    ("Lambda", 'Λ'), ("Xi", 'Ξ'), ("Pi", 'Π'), ("Sigma", 'Σ'), ("Upsilon", 'Υ'),
+    ("Phi", 'Φ'), ("Psi", 'Ψ'), ("Omega", 'Ω'), ("infty", '∞'), ("partial", '∂'),
+    ("nabla", '∇'), ("forall", '∀'), ("exists", '∃'), ("emptyset", '∅'), ("ldots", '…'),
+    ("dots", '…'), ("cdots", '⋯'), ("prime", '′'), ("hbar", 'ℏ'), ("ell", 'ℓ'),
+    ("neg", '¬'), ("angle", '∠')
+];
+
+const OPERATORS: &[(&str, char)] = &[
+    ("times", '×'), ("div", '÷'), ("cdot", '·'), ("pm", '±'), ("mp", '∓'), ("le", '≤'),
+    ("leq", '≤'), ("ge", '≥'), ("geq", '≥'), ("ne", '≠'), ("neq", '≠'), ("approx", '≈'),
+    ("equiv", '≡'), ("sim", '∼'), ("propto", '∝'), ("to", '→'), ("rightarrow", '→'),
+    ("gets", '←'), ("leftarrow", '←'), ("Rightarrow", '⇒'), ("implies", '⇒'),
+    ("Leftarrow", '⇐'), ("leftrightarrow", '↔'), ("Leftrightarrow", '⇔'), ("iff", '⇔'),
+    ("mapsto", '↦'), ("in", '∈'), ("notin", '∉'), ("subset", '⊂'), ("subseteq", '⊆'),
+    ("supset", '⊃'), ("supseteq", '⊇'), ("cup", '∪'), ("cap", '∩'), ("wedge", '∧'),
+    ("land", '∧'), ("vee", '∨'), ("lor", '∨'), ("ll", '≪'), ("gg", '≫'), ("circ", '∘')
+];
+
+const LARGE_OPERATORS: &[(&str, char)] = &[
+    ("sum", '∑'), ("prod", '∏'), ("int", '∫'), ("iint", '∬'), ("oint", '∮'),
+    ("bigcup", '⋃'), ("bigcap", '⋂')
+];
+
+fn symbol(name: &str) -> Option<Node<'static>> {
+    let find = |table: &[(&str, char)]| {
+        table.iter().find(|(command, _)| *command == name).map(|(_, c)| *c)
+    };
+    find(SYMBOLS)
+        .map(Node::Symbol)
+        .or_else(|| find(OPERATORS).map(Node::Operator))
+        .or_else(|| find(LARGE_OPERATORS).map(Node::Large))
+}
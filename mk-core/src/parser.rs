@@ -1,15 +1,95 @@
+use std::{borrow::Cow, collections::HashMap, ops::Range};
+use crate::{emoji, front_matter};
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Item<'a> {
     Newline,
     Text(Style, &'a str),
     Hyperlink(Style, &'a str, &'a str),
+    /// Columns of leading whitespace, with tabs expanded.
     Indentation(usize),
     QuoteIndent,
-    BulletPoint,
-    NumberedPoint(&'a str),
+    /// List items carry their nesting level, zero for the outermost list.
+    BulletPoint(usize),
+    NumberedPoint(usize, &'a str),
     Separator,
     CodeBlock(&'a str, &'a str),
-    Todo(bool)
+    Todo(bool),
+    /// A footnote reference: its number and the footnote's text.
+    Footnote(usize, &'a str),
+    /// `[[target|label]]`, a link to another note by name.
+    WikiLink(Style, &'a str, &'a str),
+    /// `> [!NOTE] Title` starting a quote: the kind of callout, whether it
+    /// starts open if it folds (`[!NOTE]+` or `[!NOTE]-`), and its title,
+    /// empty when it has none of its own. The rest of the quote is its body.
+    Callout(CalloutKind, Option<bool>, &'a str),
+    /// TeX, and whether it is display math (`$$..$$`) rather than inline.
+    Math(&'a str, bool),
+    /// `:tada:`: the emoji and its shortcode.
+    Emoji(Style, &'static str, &'a str),
+    /// `: ` starting a definition of the term on the line before, whose
+    /// text follows. Terms come out strong.
+    Definition,
+    /// A word defined with `*[HTML]: Hyper Text Markup Language`, and what
+    /// it stands for.
+    Abbreviation(Style, &'a str, &'a str),
+    /// Syntax read by an `Extension`: the kind it names and its text.
+    Custom(Style, &'static str, &'a str)
+}
+
+/// Syntax an application adds to the parser. Extensions are tried in the
+/// order they are given, before the built-in syntax.
+pub trait Extension: Send + Sync {
+    /// Reads a block at the start of `s`, which is a line past its quote
+    /// markers and indentation. Returns the length read, which may span
+    /// lines but should stop before the last newline, and the item.
+    fn block<'a>(&self, _s: &'a str) -> Option<(usize, Item<'a>)> {
+        None
+    }
+
+    /// Reads inline syntax at the start of `s`, in the style of the text
+    /// around it. Returns the length read and the item.
+    fn inline<'a>(&self, _s: &'a str, _style: Style) -> Option<(usize, Item<'a>)> {
+        None
+    }
+
+    /// Characters inline syntax starts with. Runs of text stop at them so
+    /// `inline` gets to see them.
+    fn triggers(&self) -> &[char] {
+        &[]
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CalloutKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution
+}
+
+impl CalloutKind {
+    fn from_label(label: &str) -> Option<Self> {
+        [
+            CalloutKind::Note,
+            CalloutKind::Tip,
+            CalloutKind::Important,
+            CalloutKind::Warning,
+            CalloutKind::Caution
+        ].into_iter().find(|kind| kind.title().eq_ignore_ascii_case(label))
+    }
+
+    /// The title shown when the callout has none of its own.
+    pub fn title(self) -> &'static str {
+        match self {
+            CalloutKind::Note => "Note",
+            CalloutKind::Tip => "Tip",
+            CalloutKind::Important => "Important",
+            CalloutKind::Warning => "Warning",
+            CalloutKind::Caution => "Caution"
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
@@ -23,23 +103,506 @@ pub struct Style {
     pub strikethrough: bool,
     pub italics: bool,
     pub small: bool,
-    pub raised: bool
+    pub raised: bool,
+    /// Subscript, from `<sub>`.
+    pub lowered: bool,
+    /// `==marked==` or `<mark>`.
+    pub highlight: bool,
+    /// A key like `<kbd>Ctrl</kbd>`, shown as a chip.
+    pub keyboard: bool,
+    /// Red, green and blue of a `<span style="color: ..">`.
+    pub color: Option<[u8; 3]>
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ParserOptions {
+    /// Columns a tab advances indentation to the next multiple of.
+    pub tab_width: usize,
+    /// Join consecutive lines into paragraphs instead of breaking after
+    /// every one. Two trailing spaces or a backslash still force a break.
+    pub paragraphs: bool,
+    pub dialect: Dialect,
+    /// Read `$..$` and `$$..$$` as math rather than small text.
+    pub math: bool
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            paragraphs: false,
+            dialect: Dialect::Marko,
+            math: false
+        }
+    }
 }
 
 pub struct Parser<'a> {
+    source: &'a str,
     s: &'a str,
+    options: ParserOptions,
     start_of_line: bool,
-    style: Style
+    style: Style,
+    /// Columns of indentation so far on this line, after any quote markers.
+    indent: usize,
+    /// Link reference definitions, keyed by their normalized label.
+    definitions: HashMap<String, &'a str>,
+    /// Footnote texts keyed by their normalized label.
+    footnotes: HashMap<String, &'a str>,
+    /// Abbreviations and their expansions, matched case-sensitively.
+    abbreviations: Vec<(&'a str, &'a str)>,
+    /// Labels and texts of the footnotes referenced so far, in the order
+    /// they are numbered.
+    referenced: Vec<(String, &'a str)>,
+    /// Labels of `[text][label]` links with no definition.
+    unresolved: Vec<&'a str>,
+    /// Quote depth and indentation of the open list items, outermost first.
+    lists: Vec<(usize, usize)>,
+    /// Quote markers seen so far on this line.
+    quote_depth: usize,
+    /// Quote depth of the line before, callouts only start a quote.
+    previous_depth: usize,
+    /// Language and depth of a code fence opened inside a quote, whose
+    /// lines come out one `CodeBlock` at a time.
+    quoted_code: Option<(&'a str, usize)>,
+    autolinks: Autolinks,
+    extensions: &'a [Box<dyn Extension>]
 }
 
 impl<'a> Parser<'a> {
     pub fn new(s: &'a str) -> Self {
-        Self {
-            s,
+        Self::with_options(s, ParserOptions::default())
+    }
+
+    /// Front matter is skipped, read it with `front_matter::parse`.
+    pub fn with_options(s: &'a str, options: ParserOptions) -> Self {
+        let body = &s[front_matter::parse(s).map_or(0, |front| front.len)..];
+        let mut parser = Self {
+            source: s,
+            s: body,
+            options,
             start_of_line: true,
-            style: Style::default()
+            style: Style::default(),
+            indent: 0,
+            definitions: definitions(body, definition),
+            footnotes: definitions(body, footnote_definition),
+            abbreviations: abbreviations(body),
+            referenced: Vec::new(),
+            unresolved: Vec::new(),
+            lists: Vec::new(),
+            quote_depth: 0,
+            previous_depth: 0,
+            quoted_code: None,
+            autolinks: Autolinks::default(),
+            extensions: &[]
+        };
+        parser.skip_lines();
+        parser
+    }
+
+    /// Reads the syntax of `extensions` too.
+    pub fn with_extensions(mut self, extensions: &'a [Box<dyn Extension>]) -> Self {
+        self.extensions = extensions;
+        self
+    }
+}
+
+/// Picks the flag of a `Style` a delimiter flips.
+type Toggle = fn(&mut Style) -> &mut bool;
+
+/// Splits a `[label]: url "title"` line into its label and url.
+fn definition(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_end();
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let (label, rest) = line[indent..].strip_prefix('[')?.split_once("]:")?;
+    if indent > 3 || label.trim().is_empty() || label.contains(['[', ']']) || label.starts_with('^') {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let (url, title) = match rest.strip_prefix('<') {
+        Some(rest) => rest.split_once('>')?,
+        None => rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len()))
+    };
+    let title = title.trim();
+    let quoted = |open, close| title.strip_prefix(open).and_then(|t: &str| t.strip_suffix(close)).is_some();
+    let titled = title.len() >= 2 && (quoted('"', '"') || quoted('\'', '\'') || quoted('(', ')'));
+    (!url.is_empty() && (title.is_empty() || titled)).then_some((label, url))
+}
+
+/// Labels match case-insensitively and regardless of inner whitespace.
+fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Splits a `[^label]: text` line into its label and text.
+fn footnote_definition(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_end();
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let (label, text) = line[indent..].strip_prefix("[^")?.split_once("]:")?;
+    let valid = indent <= 3 && !label.is_empty() && !label.contains(|c: char| c.is_whitespace() || c == '[' || c == ']');
+    valid.then_some((label, text.trim()))
+}
+
+/// Splits a `*[HTML]: Hyper Text Markup Language` line into the
+/// abbreviation and its expansion.
+fn abbreviation(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_end();
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let (label, text) = line[indent..].strip_prefix("*[")?.split_once("]:")?;
+    let text = text.trim();
+    let valid = indent <= 3 && !label.trim().is_empty() && !label.contains(['[', ']']) && !text.is_empty();
+    valid.then_some((label.trim(), text))
+}
+
+/// Lines outside code fences, where definitions can be.
+fn definition_lines(s: &str) -> impl Iterator<Item = &str> {
+    let mut in_code = false;
+    s.lines().filter(move |line| {
+        if quote_prefix(line).1.trim_start().starts_with("```") {
+            in_code = !in_code;
+            return false;
+        }
+        !in_code
+    })
+}
+
+/// Collects the definitions `parse` finds on lines outside code fences,
+/// the first of any duplicates winning.
+fn definitions<'a>(
+    s: &'a str,
+    parse: fn(&'a str) -> Option<(&'a str, &'a str)>
+) -> HashMap<String, &'a str> {
+    let mut definitions = HashMap::new();
+    for (label, value) in definition_lines(s).filter_map(parse) {
+        definitions.entry(normalize_label(label)).or_insert(value);
+    }
+    definitions
+}
+
+/// Abbreviations defined outside code fences, longest first so they win
+/// over abbreviations they contain.
+fn abbreviations(s: &str) -> Vec<(&str, &str)> {
+    let mut abbreviations: Vec<(&str, &str)> = Vec::new();
+    for (label, text) in definition_lines(s).filter_map(abbreviation) {
+        if abbreviations.iter().all(|(other, _)| *other != label) {
+            abbreviations.push((label, text));
         }
     }
+    abbreviations.sort_by_key(|(label, _)| std::cmp::Reverse(label.len()));
+    abbreviations
+}
+
+/// What follows the `: ` starting a line of a definition list.
+fn definition_marker(line: &str) -> Option<&str> {
+    let line = line.trim_start_matches(' ');
+    line.strip_prefix(": ").or_else(|| line.strip_prefix(":\t"))
+}
+
+const URL_PREFIXES: [&str; 4] = ["https://", "http://", "file://", "www."];
+
+/// Finds the first bare URL or email address in `text`. `before` is the
+/// character preceding it, links only start at word boundaries.
+pub(crate) fn find_autolink(before: Option<char>, text: &str) -> Option<Range<usize>> {
+    let mut before = before;
+    for (start, c) in text.char_indices() {
+        let boundary = before.is_none_or(|c| !c.is_alphanumeric() && !"._%+-@/:".contains(c));
+        before = Some(c);
+        if !boundary {
+            continue;
+        }
+        let rest = &text[start..];
+        if let Some(len) = url_len(rest).or_else(|| email_len(rest)) {
+            return Some(start..start + len);
+        }
+    }
+    None
+}
+
+/// Byte offsets of the last autolink search, from where it started to the
+/// end of its line, and of the link it found. Saves searching the rest of
+/// a long line again for every item on it.
+#[derive(Default)]
+pub(crate) struct Autolinks(Option<(Range<usize>, Option<Range<usize>>)>);
+
+impl Autolinks {
+    /// The first bare URL or email address from byte `at` of `source` to
+    /// the end of the line, relative to `at`.
+    pub(crate) fn next(&mut self, source: &str, at: usize) -> Option<Range<usize>> {
+        let cached = self.0.as_ref().is_some_and(|(searched, link)| {
+            let valid_until = link.as_ref().map_or(searched.end, |link| link.start);
+            searched.start <= at && at <= valid_until
+        });
+        if !cached {
+            let rest = &source[at..];
+            let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+            let before = source[..at].chars().next_back();
+            let link = find_autolink(before, line).map(|link| at + link.start..at + link.end);
+            self.0 = Some((at..at + line.len(), link));
+        }
+        let (_, link) = self.0.as_ref()?;
+        link.as_ref().map(|link| link.start - at..link.end - at)
+    }
+}
+
+/// Length of the URL `s` starts with, leaving out trailing punctuation
+/// and unbalanced closing brackets.
+fn url_len(s: &str) -> Option<usize> {
+    let prefix = URL_PREFIXES
+        .iter()
+        .find(|prefix| s.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix)))?;
+    let mut url = &s[..s.find(|c: char| c.is_whitespace() || c == '<').unwrap_or(s.len())];
+    loop {
+        let mut trimmed = url.trim_end_matches(['?', '!', '.', ',', ':', ';', '*', '_', '~', '\'', '"']);
+        for (open, close) in [('(', ')'), ('[', ']')] {
+            if trimmed.ends_with(close) && trimmed.matches(close).count() > trimmed.matches(open).count() {
+                trimmed = &trimmed[..trimmed.len() - 1];
+            }
+        }
+        if trimmed.len() == url.len() {
+            break;
+        }
+        url = trimmed;
+    }
+
+    let rest = url.get(prefix.len()..)?;
+    let valid = match *prefix {
+        "file://" => !rest.is_empty(),
+        _ => rest.starts_with(char::is_alphanumeric)
+    };
+    valid.then_some(url.len())
+}
+
+/// Length of the email address `s` starts with.
+fn email_len(s: &str) -> Option<usize> {
+    let local = s
+        .find(|c: char| !c.is_alphanumeric() && !"._%+-".contains(c))
+        .unwrap_or(s.len());
+    let domain = s.get(local..)?.strip_prefix('@').filter(|_| local > 0)?;
+    let domain = &domain[..domain
+        .find(|c: char| !c.is_alphanumeric() && c != '.' && c != '-')
+        .unwrap_or(domain.len())];
+    let domain = domain.trim_end_matches(['.', '-']);
+    let valid = domain.contains('.') && !domain.starts_with('.') && !domain.contains("..");
+    valid.then_some(local + 1 + domain.len())
+}
+
+/// Where a link goes: bare `www.` hosts get `https://` and email addresses
+/// `mailto:`.
+pub fn link_target(url: &str) -> Cow<'_, str> {
+    if url.get(..4).is_some_and(|www| www.eq_ignore_ascii_case("www.")) {
+        format!("https://{url}").into()
+    } else if !url.contains(':') && email_len(url) == Some(url.len()) {
+        format!("mailto:{url}").into()
+    } else {
+        url.into()
+    }
+}
+
+/// Which flavour of markdown to read.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Dialect {
+    /// `_` underlines, `~` strikes, `$` is small and `^` raised.
+    #[default]
+    Marko,
+    /// CommonMark with GitHub's `~~` strikethrough.
+    CommonMark
+}
+
+impl Dialect {
+    /// Emphasis delimiters and the style each one toggles, longest first.
+    fn delimiters(self) -> &'static [(&'static str, Toggle)] {
+        match self {
+            Dialect::Marko => &[
+                ("**", |style| &mut style.strong),
+                ("==", |style| &mut style.highlight),
+                ("_", |style| &mut style.underline),
+                // Other Markdown strikes through with `~~`, take it too.
+                ("~~", |style| &mut style.strikethrough),
+                ("~", |style| &mut style.strikethrough),
+                ("*", |style| &mut style.italics),
+                ("$", |style| &mut style.small),
+                ("^", |style| &mut style.raised)
+            ],
+            Dialect::CommonMark => &[
+                ("**", |style| &mut style.strong),
+                ("__", |style| &mut style.strong),
+                ("==", |style| &mut style.highlight),
+                ("~~", |style| &mut style.strikethrough),
+                ("*", |style| &mut style.italics),
+                ("_", |style| &mut style.italics)
+            ]
+        }
+    }
+}
+
+/// Whether the run of `c` starting at byte `at` of `source` can open and
+/// close emphasis, following CommonMark's flanking rules. Only `*` in
+/// CommonMark may do either inside a word.
+pub(crate) fn flanking(source: &str, at: usize, c: char, dialect: Dialect) -> (bool, bool) {
+    let before = source[..at].trim_end_matches(c).chars().next_back();
+    let after = source[at..].trim_start_matches(c).chars().next();
+    let space = |c: Option<char>| c.is_none_or(char::is_whitespace);
+    let punctuation = |c: Option<char>| c.is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace());
+
+    let left = !space(after) && (!punctuation(after) || space(before) || punctuation(before));
+    let right = !space(before) && (!punctuation(before) || space(after) || punctuation(after));
+    let intraword = dialect == Dialect::CommonMark && c == '*';
+    (
+        left && (intraword || !right || punctuation(before)),
+        right && (intraword || !left || punctuation(after))
+    )
+}
+
+/// HTML tags that style inline text, in both dialects.
+const STYLE_TAGS: [(&str, Toggle); 4] = [
+    ("sub", |style| &mut style.lowered),
+    ("sup", |style| &mut style.raised),
+    ("mark", |style| &mut style.highlight),
+    ("kbd", |style| &mut style.keyboard)
+];
+
+/// An inline tag at the start of `s` like `<kbd>` or `</sub>`, or a
+/// `<span style="color: red">`. Returns its length and the style after it.
+pub(crate) fn inline_tag(s: &str, mut style: Style) -> Option<(usize, Style)> {
+    let rest = s.strip_prefix('<')?;
+    let (closing, rest) = match rest.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, rest)
+    };
+    let end = rest.find(['>', '\n']).filter(|&end| rest[end..].starts_with('>'))?;
+    let inner = &rest[..end];
+    let len = end + 2 + closing as usize;
+
+    if let Some((_, toggle)) = STYLE_TAGS.iter().find(|(name, _)| *name == inner) {
+        *toggle(&mut style) = !closing;
+        return Some((len, style));
+    }
+    if closing {
+        return (inner == "span").then_some((len, Style { color: None, ..style }));
+    }
+    let value = inner
+        .strip_prefix("span style=")?
+        .trim_matches(['"', '\''])
+        .trim_end_matches(';')
+        .trim()
+        .strip_prefix("color:")?;
+    style.color = Some(color(value.trim())?);
+    Some((len, style))
+}
+
+/// A CSS color name or `#rgb`/`#rrggbb` hex color.
+pub fn color(name: &str) -> Option<[u8; 3]> {
+    if let Some(hex) = name.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|digit| digit as u8))
+            .collect::<Option<Vec<_>>>()?;
+        return match digits[..] {
+            [r, g, b] => Some([r * 17, g * 17, b * 17]),
+            [r1, r2, g1, g2, b1, b2] => Some([r1 * 16 + r2, g1 * 16 + g2, b1 * 16 + b2]),
+            _ => None
+        };
+    }
+    let rgb = match name.to_ascii_lowercase().as_str() {
+        "black" => [0, 0, 0],
+        "white" => [255, 255, 255],
+        "gray" | "grey" => [128, 128, 128],
+        "red" => [220, 50, 47],
+        "orange" => [230, 126, 34],
+        "yellow" => [212, 170, 0],
+        "green" => [46, 160, 67],
+        "teal" => [0, 150, 136],
+        "cyan" => [0, 172, 193],
+        "blue" => [38, 110, 220],
+        "purple" => [142, 68, 173],
+        "magenta" => [211, 54, 130],
+        "pink" => [232, 95, 150],
+        "brown" => [141, 85, 36],
+        _ => return None
+    };
+    Some(rgb)
+}
+
+/// `>` followed by a space, another `>` or the end of the line.
+fn quote_marker(s: &str) -> Option<&str> {
+    let rest = s.strip_prefix('>')?;
+    if let Some(rest) = rest.strip_prefix(' ') {
+        return Some(rest);
+    }
+    (rest.is_empty() || rest.starts_with(['>', '\n'])).then_some(rest)
+}
+
+fn is_list_marker(s: &str) -> bool {
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.starts_with("- ") || s.starts_with("* ") || (digits > 0 && s[digits..].starts_with(". "))
+}
+
+/// Whether a line, past its quote markers, is something other than
+/// paragraph text that could be joined with its neighbours.
+fn starts_block(line: &str) -> bool {
+    let line = line.trim_start_matches([' ', '\t']);
+    line.is_empty()
+        || line.starts_with(['\n', '#'])
+        || line.starts_with("```")
+        || line.starts_with("---")
+        || line.starts_with("===")
+}
+
+/// Math at the start of `s`: `$$tex$$`, `$tex$` and in Marko also
+/// `\[tex\]` and `\(tex\)`. Returns the length of the whole span, the TeX,
+/// and whether it is display math.
+pub(crate) fn math_span(s: &str, dialect: Dialect) -> Option<(usize, &str, bool)> {
+    let spans: &[(&str, &str, bool)] = match dialect {
+        Dialect::Marko => &[("$$", "$$", true), ("\\[", "\\]", true), ("\\(", "\\)", false)],
+        Dialect::CommonMark => &[("$$", "$$", true)]
+    };
+    for (open, close, display) in spans {
+        let Some(rest) = s.strip_prefix(open) else {
+            continue;
+        };
+        let tex = &rest[..rest.find(close)?];
+        return (!tex.trim().is_empty()).then_some((open.len() + tex.len() + close.len(), tex, *display));
+    }
+
+    // Inline math has to hug its dollars, and the closing one cannot be
+    // followed by a digit, so prices like $5 and $10 stay text.
+    let rest = s.strip_prefix('$')?;
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+    let end = line.match_indices('$').map(|(i, _)| i).find(|&i| {
+        !line[..i].ends_with([' ', '\t', '\\']) && !line[i + 1..].starts_with(|c: char| c.is_ascii_digit())
+    })?;
+    Some((end + 2, &rest[..end], false))
+}
+
+/// Splits a `[!KIND]` callout marker line into the kind, whether a folding
+/// callout starts open, and its title.
+fn callout(line: &str) -> Option<(CalloutKind, Option<bool>, &str)> {
+    let (label, rest) = line.strip_prefix("[!")?.split_once(']')?;
+    let kind = CalloutKind::from_label(label)?;
+    let (open, rest) = match rest.chars().next() {
+        Some('+') => (Some(true), &rest[1..]),
+        Some('-') => (Some(false), &rest[1..]),
+        _ => (None, rest)
+    };
+    let title = &rest[..rest.find('\n').unwrap_or(rest.len())];
+    if !title.is_empty() && !title.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((kind, open, title.trim()))
+}
+
+/// Number of quote markers a line starts with, and what follows them.
+fn quote_prefix(mut s: &str) -> (usize, &str) {
+    let mut depth = 0;
+    while let Some(rest) = quote_marker(s) {
+        s = rest;
+        depth += 1;
+    }
+    (depth, s)
 }
 
 impl<'a> Parser<'a> {
@@ -49,7 +612,8 @@ impl<'a> Parser<'a> {
             let number = &self.s[..n_digits];
             self.s = &self.s[(n_digits + 2)..];
             self.start_of_line = false;
-            return Some(Item::NumberedPoint(number));
+            let level = self.list_level();
+            return Some(Item::NumberedPoint(level, number));
         }
 
         None
@@ -75,6 +639,123 @@ impl<'a> Parser<'a> {
         None
     }
 
+    fn newline(&mut self) -> Item<'a> {
+        self.s = &self.s[1..];
+        self.start_of_line = true;
+        self.style = Style::default();
+        self.indent = 0;
+        self.previous_depth = self.quote_depth;
+        self.quote_depth = 0;
+        self.skip_lines();
+        Item::Newline
+    }
+
+    /// Joins the next line onto this one if both are paragraph text.
+    fn soft_break(&mut self) -> Option<Item<'a>> {
+        if !self.options.paragraphs || self.quoted_code.is_some() {
+            return None;
+        }
+        let consumed = self.source.len() - self.s.len();
+        let line_start = self.source[..consumed].rfind('\n').map_or(0, |i| i + 1);
+        let line = &self.source[line_start..consumed];
+        let (_, content) = quote_prefix(line);
+        if line.ends_with("  ") || starts_block(content) || callout(content.trim_start()).is_some() {
+            return None;
+        }
+
+        let (depth, rest) = quote_prefix(&self.s[1..]);
+        let rest = rest.trim_start_matches([' ', '\t']);
+        let next_line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        if depth != self.quote_depth
+            || starts_block(rest)
+            || is_list_marker(rest)
+            || definition(next_line).is_some()
+            || footnote_definition(next_line).is_some()
+            || abbreviation(next_line).is_some()
+            || definition_marker(next_line).is_some()
+        {
+            return None;
+        }
+
+        self.s = rest;
+        self.start_of_line = false;
+        Some(Item::Text(self.style, " "))
+    }
+
+    /// Toggles the style of an emphasis delimiter at the start of `s`.
+    fn delimiter(&mut self) -> bool {
+        let at = self.source.len() - self.s.len();
+        for (delimiter, toggle) in self.options.dialect.delimiters() {
+            let Some(rest) = self.s.strip_prefix(delimiter) else {
+                continue;
+            };
+            if self.options.math && *delimiter == "$" {
+                continue;
+            }
+            let c = delimiter.chars().next().unwrap_or_default();
+            let (open, close) = flanking(self.source, at, c, self.options.dialect);
+            let flag = toggle(&mut self.style);
+            if *flag && close || !*flag && open {
+                *flag = !*flag;
+                self.s = rest;
+                self.start_of_line = false;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Nesting level of a list item at the current indentation, closing
+    /// any deeper or sibling items before it.
+    fn list_level(&mut self) -> usize {
+        let (depth, indent) = (self.quote_depth, self.indent);
+        while self.lists.last().is_some_and(|&list| list.0 != depth || list.1 >= indent) {
+            self.lists.pop();
+        }
+        self.lists.push((depth, indent));
+        self.lists.len() - 1
+    }
+
+    /// Skips lines that render nothing: link, footnote and abbreviation
+    /// definitions, and the fence lines of code blocks inside quotes.
+    /// Called at the start of every line.
+    fn skip_lines(&mut self) {
+        loop {
+            let line_end = self.s.find('\n').map_or(self.s.len(), |i| i + 1);
+            let line = &self.s[..line_end];
+            let (depth, rest) = quote_prefix(line);
+            match self.quoted_code {
+                Some((_, code_depth)) if depth < code_depth => {
+                    self.quoted_code = None;
+                    return;
+                }
+                Some(_) if rest.starts_with("```") => self.quoted_code = None,
+                None if depth > 0 && rest.starts_with("```") => {
+                    let language = rest[3..].trim_end_matches('\n');
+                    self.quoted_code = Some((language, depth));
+                }
+                None if depth == 0
+                    && (definition(line).is_some()
+                        || footnote_definition(line).is_some()
+                        || abbreviation(line).is_some()) => {}
+                _ => return
+            }
+            self.s = &self.s[line_end..];
+        }
+    }
+
+    fn quoted_code_line(&mut self) -> Option<Item<'a>> {
+        let (language, depth) = self.quoted_code?;
+        if self.quote_depth < depth {
+            return None;
+        }
+        let end = self.s.find('\n').unwrap_or(self.s.len());
+        let code = &self.s[..end];
+        self.s = &self.s[end..];
+        self.start_of_line = false;
+        Some(Item::CodeBlock(language, code))
+    }
+
     fn inline_code(&mut self) -> Option<Item<'a>> {
         if let Some(rest) = self.s.strip_prefix('`') {
             self.s = rest;
@@ -98,6 +779,49 @@ impl<'a> Parser<'a> {
         None
     }
 
+    fn wiki_link(&mut self) -> Option<Item<'a>> {
+        let rest = self.s.strip_prefix("[[")?;
+        let inner = &rest[..rest.find("]]")?];
+        if inner.contains(['[', ']', '\n']) {
+            return None;
+        }
+        let (target, label) = inner.split_once('|').unwrap_or((inner, inner));
+        if target.trim().is_empty() {
+            return None;
+        }
+
+        self.s = &rest[inner.len() + 2..];
+        self.start_of_line = false;
+        Some(Item::WikiLink(self.style, target.trim(), label.trim()))
+    }
+
+    fn footnote(&mut self) -> Option<Item<'a>> {
+        let rest = self.s.strip_prefix("[^")?;
+        let label = &rest[..rest.find([']', '\n'])?];
+        if !rest[label.len()..].starts_with(']') {
+            return None;
+        }
+        let key = normalize_label(label);
+        let text = *self.footnotes.get(&key)?;
+
+        let number = match self.referenced.iter().position(|(other, _)| *other == key) {
+            Some(index) => index + 1,
+            None => {
+                self.referenced.push((key, text));
+                self.referenced.len()
+            }
+        };
+        self.s = &rest[label.len() + 1..];
+        self.start_of_line = false;
+        Some(Item::Footnote(number, text))
+    }
+
+    /// Texts of the footnotes referenced so far, in the order they are
+    /// numbered.
+    pub fn footnotes(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.referenced.iter().map(|(_, text)| *text)
+    }
+
     fn url(&mut self) -> Option<Item<'a>> {
         if self.s.starts_with('<') {
             let this_line = &self.s[..self.s.find('\n').unwrap_or(self.s.len())];
@@ -122,25 +846,143 @@ impl<'a> Parser<'a> {
                         return Some(Item::Hyperlink(self.style, text, url));
                     }
                 }
+
+                if let Some(item) = self.reference_link(this_line, bracket_end) {
+                    return Some(item);
+                }
             }
         }
 
         None
     }
 
+    /// `[text][label]`, `[label][]` or `[label]`, where the line starts with
+    /// `[text]` and `bracket_end` is the index of its `]`.
+    fn reference_link(&mut self, this_line: &'a str, bracket_end: usize) -> Option<Item<'a>> {
+        let text = &this_line[1..bracket_end];
+        let after = &this_line[bracket_end + 1..];
+        let (label, end) = match after.strip_prefix('[').and_then(|label| label.find(']')) {
+            Some(0) => (text, bracket_end + 3),
+            Some(label_end) => (&after[1..label_end + 1], bracket_end + label_end + 3),
+            // A shortcut, unless it is a todo box.
+            None if text != " " && text != "x" => (text, bracket_end + 1),
+            None => return None
+        };
+        let full = end > bracket_end + 1;
+
+        match self.definitions.get(&normalize_label(label)) {
+            Some(url) => {
+                self.s = &self.s[end..];
+                self.start_of_line = false;
+                Some(Item::Hyperlink(self.style, text, url))
+            }
+            // Shortcuts without a definition are just text in brackets.
+            None if full => {
+                if !self.unresolved.contains(&label) {
+                    self.unresolved.push(label);
+                }
+                self.s = &self.s[end..];
+                self.start_of_line = false;
+                Some(Item::Text(self.style, &this_line[..end]))
+            }
+            None => None
+        }
+    }
+
+    /// Labels of full and collapsed reference links found so far that have
+    /// no definition, which render as plain text.
+    pub fn unresolved(&self) -> &[&'a str] {
+        &self.unresolved
+    }
+
+    fn emoji(&mut self) -> Option<Item<'a>> {
+        let rest = self.s.strip_prefix(':')?;
+        let before = self.source[..self.source.len() - self.s.len()].chars().next_back();
+        if before.is_some_and(char::is_alphanumeric) {
+            return None;
+        }
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '_' | '+' | '-'))
+            .filter(|&len| rest[len..].starts_with(':'))?;
+        let shortcode = &rest[..len];
+        let emoji = emoji::from_shortcode(shortcode)?;
+        self.s = &rest[len + 1..];
+        self.start_of_line = false;
+        Some(Item::Emoji(self.style, emoji, shortcode))
+    }
+
+    /// The first item an extension reads at `s`, block syntax only at the
+    /// start of a line.
+    fn extension(&mut self, block: bool) -> Option<Item<'a>> {
+        let s = self.s;
+        let style = self.style;
+        let (len, item) = self.extensions.iter().find_map(|extension| {
+            let found = if block { extension.block(s) } else { extension.inline(s, style) };
+            // Reading nothing would never get past `s`.
+            found.filter(|(len, _)| *len > 0 && s.is_char_boundary(*len))
+        })?;
+        self.s = &s[len..];
+        self.start_of_line = false;
+        Some(item)
+    }
+
+    /// Whether the line at `s` is a term, with a definition on the next
+    /// line at the same quote depth.
+    fn is_term(&self) -> bool {
+        let Some((line, next)) = self.s.split_once('\n') else {
+            return false;
+        };
+        let (depth, next) = quote_prefix(next);
+        !line.trim().is_empty()
+            && definition_marker(line).is_none()
+            && depth == self.quote_depth
+            && definition_marker(next).is_some()
+    }
+
+    /// Whether the line before the one at `s` has any text, which a
+    /// definition needs for its term.
+    fn follows_text(&self) -> bool {
+        let consumed = self.source.len() - self.s.len();
+        let Some(line_start) = self.source[..consumed].rfind('\n') else {
+            return false;
+        };
+        let before = &self.source[..line_start];
+        let previous = &before[before.rfind('\n').map_or(0, |i| i + 1)..];
+        !quote_prefix(previous).1.trim().is_empty()
+    }
+
+    /// The first abbreviation in `text`, a prefix of `s`, that stands on its
+    /// own as a word. `before` is the character preceding `text`.
+    fn find_abbreviation(&self, before: Option<char>, text: &str) -> Option<(usize, &str, &'a str)> {
+        let mut found: Option<(usize, &str, &'a str)> = None;
+        for &(label, expansion) in &self.abbreviations {
+            let whole_word = |&(start, _): &(usize, &str)| {
+                let before = text[..start].chars().next_back().or(before);
+                let after = self.s[start + label.len()..].chars().next();
+                !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+            };
+            let Some((start, _)) = text.match_indices(label).find(whole_word) else {
+                continue;
+            };
+            if found.is_none_or(|(other, ..)| start < other) {
+                found = Some((start, label, expansion));
+            }
+        }
+        found
+    }
+
     fn todo(&mut self) -> Option<Item<'a>> {
         if self.s.starts_with('[') {
             let this_line = &self.s[..self.s.find('\n').unwrap_or(self.s.len())];
             if let Some(bracket_end) = this_line.find(']') {
-                let done = &this_line[1..bracket_end];
+                let done = match &this_line[1..bracket_end] {
+                    " " => false,
+                    "x" => true,
+                    _ => return None
+                };
                 self.s = &self.s[bracket_end + 1..];
                 self.start_of_line = false;
-                if done == " " {
-                    return Some(Item::Todo(false));
-                }
-                if done == "x" {
-                    return Some(Item::Todo(true))
-                }
+                return Some(Item::Todo(done));
             }
         }
 
@@ -157,32 +999,84 @@ impl<'a> Iterator for Parser<'a> {
                 return None;
             }
 
+            if self.start_of_line {
+                if let Some(item) = self.quoted_code_line() {
+                    return Some(item);
+                }
+            }
+
             if self.s.starts_with('\n') {
-                self.s = &self.s[1..];
-                self.start_of_line = true;
-                self.style = Style::default();
-                return Some(Item::Newline);
+                if let Some(item) = self.soft_break() {
+                    return Some(item);
+                }
+                return Some(self.newline());
             }
 
-            if self.s.starts_with("\\\n") && self.s.len() >= 2 {
-                self.s = &self.s[2..];
+            if let Some(rest) = self.s.strip_prefix("\\\n") {
+                // A line join in Marko, but a hard break in paragraphs and
+                // CommonMark.
+                if self.options.paragraphs || self.options.dialect == Dialect::CommonMark {
+                    self.s = &self.s[1..];
+                    return Some(self.newline());
+                }
+                self.s = rest;
                 self.start_of_line = false;
                 continue;
             }
 
-            if self.s.starts_with('\\') && self.s.len() >= 2 {
-                let text = &self.s[1..2];
-                self.s = &self.s[2..];
+            if self.options.math {
+                if let Some((len, tex, display)) = math_span(self.s, self.options.dialect) {
+                    self.s = &self.s[len..];
+                    self.start_of_line = false;
+                    return Some(Item::Math(tex, display));
+                }
+            }
+
+            if let Some(escaped) = self.s.strip_prefix('\\').and_then(|rest| rest.chars().next()) {
+                let text = &self.s[1..1 + escaped.len_utf8()];
+                self.s = &self.s[1 + escaped.len_utf8()..];
                 self.start_of_line = false;
                 return Some(Item::Text(self.style, text));
             }
 
             if self.start_of_line {
-                if self.s.starts_with(' ') {
-                    let length = self.s.find(|c| c != ' ').unwrap_or(self.s.len());
+                if self.s.starts_with([' ', '\t']) {
+                    let length = self.s.find(|c| c != ' ' && c != '\t').unwrap_or(self.s.len());
+                    let tab_width = self.options.tab_width.max(1);
+                    let start = self.indent;
+                    for c in self.s[..length].chars() {
+                        self.indent = match c {
+                            '\t' => (self.indent / tab_width + 1) * tab_width,
+                            _ => self.indent + 1
+                        };
+                    }
                     self.s = &self.s[length..];
                     self.start_of_line = true;
-                    return Some(Item::Indentation(length));
+                    // List items show their indentation as their level.
+                    if is_list_marker(self.s) {
+                        continue;
+                    }
+                    return Some(Item::Indentation(self.indent - start));
+                }
+
+                // Unindented text ends any open lists.
+                if self.indent == 0 && !is_list_marker(self.s) && quote_marker(self.s).is_none() {
+                    self.lists.clear();
+                }
+
+                if quote_marker(self.s).is_none() {
+                    if let Some(item) = self.extension(true) {
+                        return Some(item);
+                    }
+                }
+
+                if self.quote_depth > self.previous_depth {
+                    if let Some((kind, open, title)) = callout(self.s) {
+                        let end = self.s.find('\n').unwrap_or(self.s.len());
+                        self.s = &self.s[end..];
+                        self.start_of_line = false;
+                        return Some(Item::Callout(kind, open, title));
+                    }
                 }
 
                 if let Some(after) = self.s.strip_prefix("# ") {
@@ -199,17 +1093,37 @@ impl<'a> Iterator for Parser<'a> {
                     continue;
                 }
 
-                if let Some(after) = self.s.strip_prefix("> ") {
+                if self.options.dialect == Dialect::CommonMark {
+                    let hashes = self.s.find(|c| c != '#').unwrap_or(self.s.len());
+                    if (3..=6).contains(&hashes) && self.s[hashes..].starts_with(' ') {
+                        self.s = &self.s[hashes + 1..];
+                        self.start_of_line = false;
+                        self.style.subheading = true;
+                        continue;
+                    }
+                }
+
+                if let Some(after) = quote_marker(self.s) {
                     self.s = after;
                     self.start_of_line = true;
                     self.style.quoted = true;
+                    self.indent = 0;
+                    self.quote_depth += 1;
                     return Some(Item::QuoteIndent);
                 }
 
+                if self.follows_text() {
+                    if let Some(rest) = definition_marker(self.s) {
+                        self.s = rest;
+                        self.start_of_line = false;
+                        return Some(Item::Definition);
+                    }
+                }
+
                 if self.s.starts_with("- ") || self.s.starts_with("* ") {
                     self.s = &self.s[2..];
                     self.start_of_line = false;
-                    return Some(Item::BulletPoint);
+                    return Some(Item::BulletPoint(self.list_level()));
                 }
 
                 if let Some(item) = self.numbered_list() {
@@ -226,52 +1140,39 @@ impl<'a> Iterator for Parser<'a> {
                 if let Some(item) = self.code_block() {
                     return Some(item);
                 }
+
+                if self.is_term() {
+                    self.style.strong = true;
+                    self.start_of_line = false;
+                    continue;
+                }
             }
 
-            if let Some(item) = self.inline_code() {
+            if let Some(item) = self.extension(false) {
                 return Some(item);
             }
 
-            if let Some(rest) = self.s.strip_prefix("**") {
-                self.s = rest;
-                self.start_of_line = false;
-                self.style.strong = !self.style.strong;
-                continue
+            if let Some(item) = self.inline_code() {
+                return Some(item);
             }
 
-            if let Some(rest) = self.s.strip_prefix('_') {
-                self.s = rest;
-                self.start_of_line = false;
-                self.style.underline = !self.style.underline;
+            if self.delimiter() {
                 continue;
             }
 
-            if let Some(rest) = self.s.strip_prefix('~') {
-                self.s = rest;
+            if let Some((len, style)) = inline_tag(self.s, self.style) {
+                self.s = &self.s[len..];
+                self.style = style;
                 self.start_of_line = false;
-                self.style.strikethrough = !self.style.strikethrough;
                 continue;
             }
 
-            if let Some(rest) = self.s.strip_prefix('*') {
-                self.s = rest;
-                self.start_of_line = false;
-                self.style.italics = !self.style.italics;
-                continue;
-            }
-            
-            if let Some(rest) = self.s.strip_prefix('$') {
-                self.s = rest;
-                self.start_of_line = false;
-                self.style.small = !self.style.small;
-                continue;
+            if let Some(item) = self.wiki_link() {
+                return Some(item);
             }
 
-            if let Some(rest) = self.s.strip_prefix('^') {
-                self.s = rest;
-                self.start_of_line = false;
-                self.style.raised = !self.style.raised;
-                continue;
+            if let Some(item) = self.footnote() {
+                return Some(item);
             }
 
             if let Some(item) = self.url() {
@@ -282,11 +1183,43 @@ impl<'a> Iterator for Parser<'a> {
                 return Some(item);
             }
 
-            let end = self
+            if let Some(item) = self.emoji() {
+                return Some(item);
+            }
+
+            let mut end = self
                 .s
-                .find(&['*', '`', '~', '_', '/', '$', '^', '\\', '<', '[', '\n'][..])
+                .find(&['*', '`', '~', '_', '/', '$', '^', '=', '\\', '<', '[', ':', '\n'][..])
                 .map_or_else(|| self.s.len(), |special| special.max(1));
-            
+            let first = self.s.chars().next().map_or(1, char::len_utf8);
+            for extension in self.extensions {
+                if let Some(trigger) = self.s.get(first..end).and_then(|run| run.find(extension.triggers())) {
+                    end = first + trigger;
+                }
+            }
+
+            let at = self.source.len() - self.s.len();
+            let before = self.source[..at].chars().next_back();
+            if let Some(link) = self.autolinks.next(self.source, at) {
+                if link.start == 0 {
+                    let url = &self.s[link];
+                    self.s = &self.s[url.len()..];
+                    self.start_of_line = false;
+                    return Some(Item::Hyperlink(self.style, url, url));
+                }
+                end = end.min(link.start);
+            }
+
+            if let Some((start, label, expansion)) = self.find_abbreviation(before, &self.s[..end]) {
+                if start == 0 {
+                    let text = &self.s[..label.len()];
+                    self.s = &self.s[label.len()..];
+                    self.start_of_line = false;
+                    return Some(Item::Abbreviation(self.style, text, expansion));
+                }
+                end = start;
+            }
+
             let item = Item::Text(self.style, &self.s[..end]);
             self.s = &self.s[end..];
             self.start_of_line = false;
@@ -294,3 +1227,91 @@ impl<'a> Iterator for Parser<'a> {
         }
     }
 }
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Heading {
+    pub level: usize,
+    pub text: String,
+    /// Zero based line the heading is on.
+    pub line: usize,
+    /// Anchor `#fragment` links use, unique within the document.
+    pub slug: String
+}
+
+/// Every heading in `s`, in order.
+pub fn headings(s: &str, options: ParserOptions) -> Vec<Heading> {
+    let mut headings: Vec<Heading> = Vec::new();
+    let mut line = 0;
+
+    for item in Parser::with_options(s, options) {
+        // `source` is where the text is in `s`, an emoji's glyph is not.
+        let (style, text, source) = match item {
+            Item::Text(style, text)
+            | Item::Hyperlink(style, text, _)
+            | Item::WikiLink(style, _, text)
+            | Item::Abbreviation(style, text, _) => (style, text, text),
+            Item::Emoji(style, emoji, shortcode) => (style, emoji, shortcode),
+            _ => continue
+        };
+        let level = if style.heading {
+            1
+        } else if style.subheading {
+            2
+        } else {
+            continue;
+        };
+
+        if let Some(offset) = offset_in(s, source) {
+            line = s[..offset].matches('\n').count();
+        }
+        match headings.last_mut() {
+            Some(heading) if heading.line == line => heading.text.push_str(text),
+            _ => headings.push(Heading {
+                level,
+                text: text.to_string(),
+                line,
+                slug: String::new()
+            })
+        }
+    }
+
+    let mut used = HashMap::new();
+    for heading in &mut headings {
+        heading.text = heading.text.trim().to_string();
+        let slug = slug(&heading.text);
+        // Repeated headings get -1, -2, ... like on GitHub.
+        let count = used.entry(slug.clone()).or_insert(0);
+        heading.slug = match *count {
+            0 => slug,
+            n => format!("{slug}-{n}")
+        };
+        *count += 1;
+    }
+    headings
+}
+
+/// GitHub style anchor for a heading: lowercase, punctuation dropped and
+/// spaces turned into dashes.
+pub fn slug(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .map(|c| if c == ' ' { '-' } else { c })
+        .collect()
+}
+
+/// Targets of the `[[wiki links]]` in `s`.
+pub fn wiki_links(s: &str) -> impl Iterator<Item = &str> {
+    Parser::new(s).filter_map(|item| match item {
+        Item::WikiLink(_, target, _) => Some(target),
+        _ => None
+    })
+}
+
+/// Byte offset of `text` in `s`, if it is a slice of it.
+pub fn offset_in(s: &str, text: &str) -> Option<usize> {
+    let start = s.as_ptr() as usize;
+    let offset = (text.as_ptr() as usize).checked_sub(start)?;
+    (offset + text.len() <= s.len()).then_some(offset)
+}
@@ -0,0 +1,178 @@
+use std::{
+    fs::{self, File, TryLockError},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::SystemTime
+};
+
+const HEADER: &str = "marko-recovery: ";
+/// Written to an instance's lock file when it exits normally.
+const CLEAN: &str = "clean";
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Debug)]
+pub struct Recovered {
+    pub id: String,
+    pub original: Option<PathBuf>,
+    pub code: String,
+    pub modified: Option<SystemTime>,
+    /// Whether the instance that wrote it exited normally rather than
+    /// crashing.
+    pub clean: bool
+}
+
+/// A new recovery id, owned by this process. Ids start with the process id
+/// so other instances can tell whose buffers they are.
+pub fn new_id() -> String {
+    format!("{}-{}", std::process::id(), NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Held while this instance runs, so other instances leave its buffers
+/// alone.
+pub struct Lock {
+    file: File
+}
+
+impl Lock {
+    /// Marks the exit as clean. Buffers still in the recovery directory
+    /// had unsaved changes rather than being cut off by a crash.
+    pub fn release(mut self) -> io::Result<()> {
+        self.file.write_all(CLEAN.as_bytes())
+    }
+}
+
+/// Takes this process's lock in `dir`, and removes the lock files of
+/// instances that are gone and left no buffers behind.
+pub fn lock(dir: &Path) -> io::Result<Lock> {
+    fs::create_dir_all(dir)?;
+    let owners = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lock"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect::<Vec<_>>();
+    let ids = list_files(dir)?;
+    for owner in owners {
+        let left = ids.iter().any(|(id, _)| self::owner(id) == owner);
+        if !left && owner_state(dir, &owner)?.is_some() {
+            fs::remove_file(lock_file(dir, &owner))?;
+        }
+    }
+
+    let file = File::create(lock_file(dir, &std::process::id().to_string()))?;
+    file.try_lock().map_err(|error| match error {
+        TryLockError::Error(error) => error,
+        TryLockError::WouldBlock => io::Error::new(io::ErrorKind::WouldBlock, "recovery lock is taken")
+    })?;
+    Ok(Lock { file })
+}
+
+pub fn recovery_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("marko").join("recovery"))
+}
+
+pub fn store(
+    dir: &Path,
+    id: &str,
+    original: Option<&Path>,
+    code: &str
+) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    let original = original.map_or_else(String::new, |path| path.display().to_string());
+    let contents = format!("{HEADER}{original}\n{code}");
+
+    // Write next to the final file and rename over it, so a crash halfway
+    // through never leaves a truncated recovery buffer behind.
+    let temporary = dir.join(format!("{id}.tmp"));
+    fs::write(&temporary, contents)?;
+    fs::rename(temporary, file_name(dir, id))
+}
+
+pub fn remove(dir: &Path, id: &str) -> io::Result<()> {
+    match fs::remove_file(file_name(dir, id)) {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result
+    }
+}
+
+/// Buffers left behind by instances that are no longer running, most
+/// recently autosaved first.
+pub fn list(dir: &Path) -> io::Result<Vec<Recovered>> {
+    let mut recovered = Vec::new();
+    for (id, path) in list_files(dir)? {
+        // Buffers of a running instance are still open there.
+        let Some(clean) = owner_state(dir, owner(&id))? else {
+            continue;
+        };
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(contents) = contents.strip_prefix(HEADER) else {
+            continue;
+        };
+
+        let (original, code) = contents.split_once('\n').unwrap_or((contents, ""));
+        recovered.push(Recovered {
+            id: id.to_string(),
+            original: (!original.is_empty()).then(|| PathBuf::from(original)),
+            code: code.to_string(),
+            modified: fs::metadata(&path).and_then(|metadata| metadata.modified()).ok(),
+            clean
+        });
+    }
+
+    recovered.sort_by_key(|recovered| std::cmp::Reverse(recovered.modified));
+    Ok(recovered)
+}
+
+/// Ids and paths of the recovery files in `dir`.
+fn list_files(dir: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let entries = match fs::read_dir(dir) {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        entries => entries?
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "md") {
+            continue;
+        }
+        if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+            files.push((id.to_string(), path.clone()));
+        }
+    }
+    Ok(files)
+}
+
+fn owner(id: &str) -> &str {
+    id.split('-').next().unwrap_or(id)
+}
+
+/// None while the instance that owns buffers is running, otherwise
+/// whether it exited cleanly.
+fn owner_state(dir: &Path, owner: &str) -> io::Result<Option<bool>> {
+    let path = lock_file(dir, owner);
+    let file = match File::open(&path) {
+        // Written before instances took locks, or after a crash in between.
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Some(false)),
+        file => file?
+    };
+    match file.try_lock_shared() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => return Ok(None),
+        Err(TryLockError::Error(error)) => return Err(error)
+    }
+    Ok(Some(fs::read_to_string(path)? == CLEAN))
+}
+
+fn lock_file(dir: &Path, owner: &str) -> PathBuf {
+    dir.join(format!("{owner}.lock"))
+}
+
+fn file_name(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.md"))
+}
@@ -0,0 +1,199 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Sender},
+        Arc, Mutex
+    },
+    thread
+};
+use regex::{Regex, RegexBuilder};
+use crate::{parser, workspace};
+
+/// Stop collecting once this many lines matched, a query like `e` would
+/// otherwise list every line of the workspace.
+const MAX_MATCHES: usize = 1000;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Query {
+    pub text: String,
+    pub regex: bool,
+    pub case_sensitive: bool
+}
+
+#[derive(Clone, Debug)]
+pub struct LineMatch {
+    /// Zero based line number.
+    pub line: usize,
+    pub text: String,
+    /// Byte ranges of the matches within `text`.
+    pub ranges: Vec<Range<usize>>
+}
+
+#[derive(Clone, Debug)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    pub lines: Vec<LineMatch>
+}
+
+/// What the index keeps of a file, parsed once when it is read.
+struct Document {
+    contents: String,
+    headings: Vec<parser::Heading>,
+    /// Targets of its `[[wiki links]]`.
+    links: Vec<String>
+}
+
+impl Document {
+    fn new(contents: String) -> Self {
+        let headings = parser::headings(&contents, Default::default());
+        let links = parser::wiki_links(&contents).map(str::to_string).collect();
+        Document { contents, headings, links }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    files: Mutex<BTreeMap<PathBuf, Document>>,
+    pending: AtomicUsize,
+    generation: AtomicU64
+}
+
+/// Contents of every file in a workspace, read on a background thread so
+/// opening a large folder never blocks the UI.
+pub struct Index {
+    shared: Arc<Shared>,
+    sender: Sender<PathBuf>
+}
+
+impl Index {
+    pub fn new(
+        files: impl IntoIterator<Item = PathBuf>,
+        on_update: impl Fn() + Send + 'static
+    ) -> Self {
+        let shared = Arc::new(Shared::default());
+        let (sender, receiver) = mpsc::channel::<PathBuf>();
+
+        let worker = Arc::clone(&shared);
+        thread::spawn(move || {
+            for path in receiver {
+                let document = fs::read_to_string(&path).ok().map(Document::new);
+                {
+                    let mut files = worker.files.lock().unwrap_or_else(|error| error.into_inner());
+                    match document {
+                        Some(document) => files.insert(path, document),
+                        None => files.remove(&path)
+                    };
+                }
+                worker.pending.fetch_sub(1, Ordering::Relaxed);
+                worker.generation.fetch_add(1, Ordering::Relaxed);
+                on_update();
+            }
+        });
+
+        let index = Index { shared, sender };
+        for path in files {
+            index.update(path);
+        }
+        index
+    }
+
+    /// Queues `path` to be read again, or dropped if it no longer exists.
+    pub fn update(&self, path: PathBuf) {
+        self.shared.pending.fetch_add(1, Ordering::Relaxed);
+        if self.sender.send(path).is_err() {
+            self.shared.pending.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_indexing(&self) -> bool {
+        self.shared.pending.load(Ordering::Relaxed) > 0
+    }
+
+    /// Changes every time a file is indexed, so callers know when cached
+    /// results are stale.
+    pub fn generation(&self) -> u64 {
+        self.shared.generation.load(Ordering::Relaxed)
+    }
+
+    pub fn search(&self, query: &Query) -> Result<Vec<FileMatches>, regex::Error> {
+        if query.text.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pattern = if query.regex {
+            query.text.clone()
+        } else {
+            regex::escape(&query.text)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!query.case_sensitive)
+            .build()?;
+
+        let mut results = Vec::new();
+        let mut total = 0;
+        for (path, document) in self.files().iter() {
+            let lines = search_file(&regex, &document.contents, MAX_MATCHES - total);
+            if lines.is_empty() {
+                continue;
+            }
+            total += lines.len();
+            results.push(FileMatches {
+                path: path.clone(),
+                lines
+            });
+            if total >= MAX_MATCHES {
+                break;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Notes with a `[[wiki link]]` to `path`, other than itself.
+    pub fn backlinks(&self, path: &Path) -> Vec<PathBuf> {
+        self.files()
+            .iter()
+            .filter(|(other, _)| *other != path)
+            .filter(|(_, document)| {
+                document.links.iter().any(|target| workspace::links_to(target, path))
+            })
+            .map(|(other, _)| other.clone())
+            .collect()
+    }
+
+    /// Every heading of every indexed file, with the file it is in.
+    pub fn headings(&self) -> Vec<(PathBuf, parser::Heading)> {
+        self.files()
+            .iter()
+            .flat_map(|(path, document)| {
+                document.headings.iter().map(|heading| (path.clone(), heading.clone()))
+            })
+            .collect()
+    }
+
+    fn files(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, Document>> {
+        self.shared.files.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+fn search_file(regex: &Regex, contents: &str, limit: usize) -> Vec<LineMatch> {
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let ranges = regex
+                .find_iter(text)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect::<Vec<_>>();
+            (!ranges.is_empty()).then(|| LineMatch {
+                line,
+                text: text.to_string(),
+                ranges
+            })
+        })
+        .take(limit)
+        .collect()
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver}
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+
+/// Watches open files for changes made by other programs.
+///
+/// Tools like git replace files instead of writing them in place, which
+/// drops an inotify watch on the file itself, so the parent directory is
+/// watched and events are filtered down to the files of interest.
+pub struct Watcher {
+    inner: RecommendedWatcher,
+    events: Receiver<PathBuf>,
+    files: HashSet<PathBuf>,
+    dirs: HashSet<PathBuf>,
+    trees: HashSet<PathBuf>
+}
+
+impl Watcher {
+    pub fn new(on_event: impl Fn() + Send + 'static) -> notify::Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let inner = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if !(event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove()) {
+                return;
+            }
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+            on_event();
+        })?;
+
+        Ok(Watcher {
+            inner,
+            events,
+            files: HashSet::new(),
+            dirs: HashSet::new(),
+            trees: HashSet::new()
+        })
+    }
+
+    pub fn watch(&mut self, path: &Path) -> notify::Result<()> {
+        let path = path.canonicalize()?;
+        if let Some(dir) = path.parent() {
+            if !self.dirs.contains(dir) && !self.in_tree(dir) {
+                self.inner.watch(dir, RecursiveMode::NonRecursive)?;
+                self.dirs.insert(dir.to_path_buf());
+            }
+        }
+        self.files.insert(path);
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self, path: &Path) -> notify::Result<()> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.files.remove(&path);
+
+        if let Some(dir) = path.parent() {
+            let still_used = self.files.iter().any(|file| file.parent() == Some(dir));
+            if !still_used && self.dirs.remove(dir) {
+                self.inner.unwatch(dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Watches a whole folder, reporting changes to anything inside it.
+    pub fn watch_tree(&mut self, dir: &Path) -> notify::Result<()> {
+        let dir = dir.canonicalize()?;
+        if self.trees.contains(&dir) {
+            return Ok(());
+        }
+        // The tree covers folders watched for open files inside it, and
+        // watching them too would report every change twice.
+        let covered = self.dirs
+            .iter()
+            .filter(|watched| watched.starts_with(&dir))
+            .cloned()
+            .collect::<Vec<_>>();
+        for watched in covered {
+            self.dirs.remove(&watched);
+            self.inner.unwatch(&watched)?;
+        }
+        self.inner.watch(&dir, RecursiveMode::Recursive)?;
+        self.trees.insert(dir);
+        Ok(())
+    }
+
+    pub fn unwatch_tree(&mut self, dir: &Path) -> notify::Result<()> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        if self.trees.remove(&dir) {
+            self.inner.unwatch(&dir)?;
+        }
+
+        // Open files in the folder relied on it, watch their parents again.
+        let parents = self.files
+            .iter()
+            .filter_map(|file| file.parent())
+            .filter(|parent| !self.dirs.contains(*parent) && !self.in_tree(parent))
+            .map(Path::to_path_buf)
+            .collect::<HashSet<_>>();
+        for parent in parents {
+            self.inner.watch(&parent, RecursiveMode::NonRecursive)?;
+            self.dirs.insert(parent);
+        }
+        Ok(())
+    }
+
+    fn in_tree(&self, path: &Path) -> bool {
+        self.trees.iter().any(|tree| path.starts_with(tree))
+    }
+
+    /// Drains pending events, returning each watched file that changed and
+    /// anything that changed inside a watched folder.
+    pub fn changed(&self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for path in self.events.try_iter() {
+            let watched = self.files.contains(&path) || self.in_tree(&path);
+            if watched && !changed.contains(&path) {
+                changed.push(path);
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf}
+};
+use crate::parser;
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub name: String,
+    /// `Some` for folders, holding their filtered contents.
+    pub children: Option<Vec<Entry>>
+}
+
+impl Entry {
+    pub fn is_dir(&self) -> bool {
+        self.children.is_some()
+    }
+}
+
+pub struct Workspace {
+    root: PathBuf,
+    pub markdown_only: bool,
+    pub entries: Vec<Entry>
+}
+
+impl Workspace {
+    pub fn open(root: PathBuf) -> io::Result<Self> {
+        let root = root.canonicalize()?;
+        let mut workspace = Workspace {
+            root,
+            markdown_only: true,
+            entries: Vec::new()
+        };
+        workspace.refresh()?;
+        Ok(workspace)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn name(&self) -> String {
+        self.root
+            .file_name()
+            .map_or_else(|| self.root.display().to_string(), |name| name.to_string_lossy().into_owned())
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.root)
+    }
+
+    pub fn refresh(&mut self) -> io::Result<()> {
+        self.entries = scan(&self.root, self.markdown_only)?;
+        Ok(())
+    }
+
+    /// Every file shown in the tree, depth first.
+    pub fn files(&self) -> Vec<&Path> {
+        fn collect<'a>(entries: &'a [Entry], files: &mut Vec<&'a Path>) {
+            for entry in entries {
+                match &entry.children {
+                    Some(children) => collect(children, files),
+                    None => files.push(&entry.path)
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        collect(&self.entries, &mut files);
+        files
+    }
+
+    /// The note a `[[wiki link]]` target refers to, if there is one.
+    pub fn find_note(&self, target: &str) -> Option<&Path> {
+        self.files().into_iter().find(|path| links_to(target, path))
+    }
+}
+
+/// Whether a `[[wiki link]]` target names the note at `path`. Notes are
+/// named by their file name without extension, ignoring case and any
+/// folders in the target.
+pub fn links_to(target: &str, path: &Path) -> bool {
+    let target = target.trim();
+    let target = target.rsplit('/').next().unwrap_or(target);
+    let target = target.strip_suffix(".md").unwrap_or(target);
+    is_markdown(path)
+        && path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.eq_ignore_ascii_case(target))
+}
+
+/// Where to create the note a `[[wiki link]]` target names, in `dir` or
+/// a folder under it. Targets that are absolute or climb out of `dir`
+/// with `..` get none.
+pub fn new_note_path(dir: &Path, target: &str) -> Option<PathBuf> {
+    let target = target.trim();
+    let name = target.strip_suffix(".md").unwrap_or(target);
+    let inside = Path::new(name)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    (inside && !name.is_empty()).then(|| dir.join(format!("{name}.md")))
+}
+
+/// Splits a link to a local Markdown file, like `../notes/todo.md#today`,
+/// into its path and fragment. Links with a scheme other than `file:` are
+/// left to the browser, as are bare `www.` hosts and email addresses like
+/// `www.gov.md` or `me@example.md`.
+pub fn note_link(url: &str) -> Option<(PathBuf, Option<&str>)> {
+    if parser::link_target(url) != url {
+        return None;
+    }
+    let (path, fragment) = match url.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (url, None)
+    };
+    let path = path.strip_prefix("file://").unwrap_or(path);
+    if path.contains(':') && !Path::new(path).is_absolute() {
+        return None;
+    }
+    let path = PathBuf::from(path.replace("%20", " "));
+    is_markdown(&path).then_some((path, fragment))
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown"))
+}
+
+fn scan(dir: &Path, markdown_only: bool) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+        let children = if entry.file_type()?.is_dir() {
+            Some(scan(&path, markdown_only)?)
+        } else if markdown_only && !is_markdown(&path) {
+            continue;
+        } else {
+            None
+        };
+        entries.push(Entry { path, name, children });
+    }
+
+    entries.sort_by(|a, b| {
+        b.is_dir()
+            .cmp(&a.is_dir())
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(entries)
+}
+
+pub fn create_file(path: &Path) -> io::Result<()> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map(drop)
+}
+
+pub fn create_dir(path: &Path) -> io::Result<()> {
+    fs::create_dir(path)
+}
+
+/// Renames or moves `from` to `to`, refusing to overwrite anything.
+pub fn rename(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display())
+        ));
+    }
+    if to.starts_with(from) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot move a folder into itself"
+        ));
+    }
+    fs::rename(from, to)
+}
+
+pub fn delete(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
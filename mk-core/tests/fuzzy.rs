@@ -0,0 +1,30 @@
+use mk_core::fuzzy::fuzzy_match;
+
+#[test]
+fn matches_subsequences_ignoring_case_and_spaces() {
+    let found = fuzzy_match("R me", "notes/README.md").unwrap();
+    assert_eq!(found.positions, vec![6, 10, 11]);
+    assert!(fuzzy_match("", "anything").is_some());
+    assert!(fuzzy_match("xyz", "notes/README.md").is_none());
+    assert!(fuzzy_match("dr", "readme").is_none());
+}
+
+#[test]
+fn matches_chars_that_lowercase_to_several() {
+    // `İ` lowercases to `i` and a combining dot.
+    assert_eq!(fuzzy_match("İ", "İstanbul").unwrap().positions, vec![0]);
+    assert_eq!(fuzzy_match("İs", "xİstanbul").unwrap().positions, vec![1, 2]);
+    assert_eq!(fuzzy_match("is", "İstanbul").unwrap().positions, vec![0, 1]);
+    assert!(fuzzy_match("İ", "istanbul").is_none());
+}
+
+#[test]
+fn ranks_tight_and_word_start_matches_higher() {
+    let score = |pattern, candidate| fuzzy_match(pattern, candidate).unwrap().score;
+
+    assert!(score("read", "readme.md") > score("read", "r-e-a-d.md"));
+    assert!(score("nm", "new meeting.md") > score("nm", "document.md"));
+    assert!(score("todo", "todo.md") > score("todo", "archive/old/todo.md"));
+    // Camel case humps count as word starts.
+    assert!(score("qo", "QuickOpen.md") > score("qo", "quotes.md"));
+}
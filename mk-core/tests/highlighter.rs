@@ -0,0 +1,47 @@
+use mk_core::eframe::egui;
+use mk_core::parser::ParserOptions;
+use mk_core::Highlighter;
+use proptest::prelude::*;
+
+fn highlight(s: &str) -> String {
+    Highlighter::default()
+        .highlight(&egui::Style::default(), s, Default::default())
+        .text
+}
+
+fn highlight_math(s: &str) -> String {
+    let options = ParserOptions { math: true, ..Default::default() };
+    Highlighter::default()
+        .highlight(&egui::Style::default(), s, options)
+        .text
+}
+
+proptest! {
+    #[test]
+    fn keeps_every_character(s in any::<String>()) {
+        prop_assert_eq!(highlight(&s), s);
+    }
+
+    #[test]
+    fn keeps_every_character_of_markdown(s in "([a-zé漢🦀 \n]|[*_~`$^<>\\\\\\[\\]#>-]|https://|www\\.|[@.:]){0,64}") {
+        prop_assert_eq!(highlight(&s), s);
+    }
+
+    #[test]
+    fn keeps_every_character_of_math(s in "([a-z ]|[$\\\\()\\[\\]{}^_\n]){0,64}") {
+        prop_assert_eq!(highlight_math(&s), s);
+    }
+}
+
+#[test]
+fn escapes_multibyte_characters() {
+    assert_eq!(highlight("\\é \\🦀"), "\\é \\🦀");
+}
+
+#[test]
+fn highlights_long_lines_in_linear_time() {
+    let line = "plain words: and/or more ".repeat(20_000) + "www.example.com";
+    let start = std::time::Instant::now();
+    assert_eq!(highlight(&line), line);
+    assert!(start.elapsed() < std::time::Duration::from_secs(2), "took {:?}", start.elapsed());
+}
@@ -0,0 +1,44 @@
+use mk_core::math::{parse, Node};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn never_panics(s in any::<String>()) {
+        parse(&s);
+    }
+
+    #[test]
+    fn never_panics_on_tex(s in "([a-z0-9 ]|[{}^_\\\\]|\\\\frac|\\\\sqrt|\\\\text|\\\\alpha|\\\\left|\\\\,){0,64}") {
+        parse(&s);
+    }
+}
+
+#[test]
+fn reads_fractions_and_scripts() {
+    let nodes = parse(r"\frac{1}{x_i^2} \le \sqrt\alpha");
+    assert_eq!(nodes, [
+        Node::Fraction(vec![Node::Symbol('1')], vec![Node::Scripts {
+            base: Box::new(Node::Symbol('x')),
+            sub: Some(vec![Node::Symbol('i')]),
+            sup: Some(vec![Node::Symbol('2')])
+        }]),
+        Node::Operator('≤'),
+        Node::Root(vec![Node::Symbol('α')])
+    ]);
+
+    assert_eq!(parse(r"\sin \text{if } \unknown"), [
+        Node::Text("sin"),
+        Node::Text("if "),
+        Node::Text(r"\unknown")
+    ]);
+}
+
+#[test]
+fn deep_nesting_stays_flat() {
+    let deep = "{".repeat(100_000);
+    parse(&deep);
+    parse(&r"\sqrt".repeat(100_000));
+    parse(&r"\frac{".repeat(100_000));
+    parse(&r"x^\sqrt".repeat(100_000));
+    parse(&"^2".repeat(100_000));
+}
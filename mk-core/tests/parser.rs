@@ -0,0 +1,559 @@
+use std::sync::{Arc, Mutex};
+use mk_core::front_matter::{self, Format, Value};
+use mk_core::parser::{
+    color, headings, link_target, slug, wiki_links,
+    CalloutKind, Dialect, Extension, Item, Parser, ParserOptions, Style
+};
+use proptest::prelude::*;
+
+/// Everything an item shows, in source order.
+fn item_text(item: Item) -> String {
+    match item {
+        Item::Text(_, text) => text.to_string(),
+        Item::Hyperlink(_, text, url) => format!("{text}{url}"),
+        Item::WikiLink(_, target, label) => format!("{target}{label}"),
+        Item::NumberedPoint(_, number) => number.to_string(),
+        Item::CodeBlock(_, code) => code.to_string(),
+        Item::Todo(true) => "x".to_string(),
+        Item::Footnote(number, text) => format!("{number}{text}"),
+        Item::Callout(_, _, title) => title.to_string(),
+        Item::Math(tex, _) => tex.to_string(),
+        Item::Emoji(_, _, shortcode) => shortcode.to_string(),
+        Item::Abbreviation(_, text, _) => text.to_string(),
+        Item::Custom(_, _, text) => text.to_string(),
+        Item::Newline => "\n".to_string(),
+        _ => String::new()
+    }
+}
+
+fn rendered(s: &str) -> String {
+    Parser::new(s).map(item_text).collect()
+}
+
+/// Markup characters, front matter, code fences, link and abbreviation
+/// definitions, footnote labels, callout kinds and style tags are free to
+/// disappear, but every other letter and digit of the input has to show
+/// up, in order.
+fn covers(output: &str, input: &str) -> bool {
+    let tags = regex::Regex::new(r#"</?(sub|sup|mark|kbd|span)>|<span style=[^>\n]*>"#).unwrap();
+    let body = &input[front_matter::parse(input).map_or(0, |front| front.len)..];
+    let mut output = output.chars().filter(|c| c.is_alphanumeric());
+    tags.replace_all(body, "")
+        .split('\n')
+        .filter(|line| !line.trim_start_matches(['>', ' ']).starts_with("```"))
+        .filter(|line| {
+            let line = line.trim_start();
+            !(line.strip_prefix('*').unwrap_or(line).starts_with('[') && line.contains("]:"))
+        })
+        .flat_map(|line| line.split('[').enumerate().map(|(i, part)| match i {
+            0 => part,
+            _ if part.starts_with(['^', '!']) => &part[part.find(']').map_or(0, |end| end + 1)..],
+            _ => part
+        }))
+        .flat_map(str::chars)
+        .filter(|c| c.is_alphanumeric())
+        .all(|c| output.any(|o| o == c))
+}
+
+fn markdown() -> impl Strategy<Value = String> {
+    let pieces = prop_oneof![
+        "[a-zA-Z0-9 ]{1,8}",
+        "[é漢🦀ß]{1,3}",
+        Just("\n".to_string()),
+        Just("\\".to_string()),
+        Just("# ".to_string()),
+        Just("## ".to_string()),
+        Just("> ".to_string()),
+        Just("- ".to_string()),
+        Just("1. ".to_string()),
+        Just("```".to_string()),
+        Just("---".to_string()),
+        Just("[ ]".to_string()),
+        Just("[x]".to_string()),
+        Just("https://".to_string()),
+        Just("www.".to_string()),
+        "[@.:]",
+        "[*_~`$^<>\\[\\]()/]"
+    ];
+    prop::collection::vec(pieces, 0..32).prop_map(|pieces| pieces.concat())
+}
+
+proptest! {
+    #[test]
+    fn never_panics(s in any::<String>()) {
+        for _ in Parser::new(&s) {}
+    }
+
+    #[test]
+    fn never_panics_on_markdown(s in markdown()) {
+        for _ in Parser::new(&s) {}
+    }
+
+    #[test]
+    fn covers_input(s in any::<String>()) {
+        prop_assert!(covers(&rendered(&s), &s));
+    }
+
+    #[test]
+    fn covers_markdown(s in markdown()) {
+        prop_assert!(covers(&rendered(&s), &s), "{:?}", rendered(&s));
+    }
+
+    #[test]
+    fn covers_markdown_with_options(
+        s in markdown(),
+        tab_width in 0..8usize,
+        paragraphs in any::<bool>(),
+        commonmark in any::<bool>(),
+        math in any::<bool>()
+    ) {
+        let options = ParserOptions {
+            tab_width,
+            paragraphs,
+            dialect: if commonmark { Dialect::CommonMark } else { Dialect::Marko },
+            math
+        };
+        let rendered = Parser::with_options(&s, options).map(item_text).collect::<String>();
+        prop_assert!(covers(&rendered, &s), "{:?}", rendered);
+    }
+
+    #[test]
+    fn plain_text_is_unchanged(s in "[a-zA-Zé漢🦀][a-zA-Zé漢🦀 ,.!?]*") {
+        prop_assert_eq!(rendered(&s), s);
+    }
+}
+
+#[test]
+fn escapes_multibyte_characters() {
+    let items = Parser::new("\\é\\🦀").collect::<Vec<_>>();
+    assert!(matches!(items[..], [Item::Text(_, "é"), Item::Text(_, "🦀")]));
+}
+
+#[test]
+fn keeps_brackets_that_are_not_todos() {
+    assert_eq!(rendered("[foo] bar"), "[foo] bar");
+}
+
+#[test]
+fn nests_quotes() {
+    let items = Parser::new("> > deep\n>\n> - item").collect::<Vec<_>>();
+    assert!(matches!(items[..], [
+        Item::QuoteIndent, Item::QuoteIndent, Item::Text(_, "deep"), Item::Newline,
+        Item::QuoteIndent, Item::Newline,
+        Item::QuoteIndent, Item::BulletPoint(0), Item::Text(_, "item")
+    ]));
+}
+
+#[test]
+fn code_blocks_in_quotes() {
+    let items = Parser::new("> ```rust\n> let a = 1;\n>\n> ```\n> after").collect::<Vec<_>>();
+    assert!(matches!(items[..], [
+        Item::QuoteIndent, Item::CodeBlock("rust", "let a = 1;"), Item::Newline,
+        Item::QuoteIndent, Item::CodeBlock("rust", ""), Item::Newline,
+        Item::QuoteIndent, Item::Text(_, "after")
+    ]), "{items:?}");
+}
+
+#[test]
+fn nests_lists() {
+    let items = Parser::new("- a\n  1. b\n\t- [x] c\n    d\n- e\nf\n  - g")
+        .filter(|item| !matches!(item, Item::Text(..) | Item::Newline))
+        .collect::<Vec<_>>();
+    assert_eq!(items, [
+        Item::BulletPoint(0),
+        Item::NumberedPoint(1, "1"),
+        Item::BulletPoint(2),
+        Item::Todo(true),
+        Item::Indentation(4),
+        Item::BulletPoint(0),
+        Item::BulletPoint(0)
+    ]);
+}
+
+#[test]
+fn expands_tabs() {
+    let options = ParserOptions {
+        tab_width: 2,
+        ..Default::default()
+    };
+    let items = Parser::with_options(" \tx", options).collect::<Vec<_>>();
+    assert!(matches!(items[..], [Item::Indentation(2), Item::Text(_, "x")]));
+}
+
+#[test]
+fn joins_paragraph_lines() {
+    let options = ParserOptions {
+        paragraphs: true,
+        ..Default::default()
+    };
+    let text = |s| Parser::with_options(s, options)
+        .map(|item| match item {
+            Item::Text(_, text) => text,
+            Item::Newline => "\n",
+            _ => ""
+        })
+        .collect::<String>();
+
+    assert_eq!(text("one\ntwo\n\nthree"), "one two\n\nthree");
+    assert_eq!(text("hard  \nbreak\\\nhere"), "hard  \nbreak\nhere");
+    assert_eq!(text("# title\ntext\n- item\n  more\n- next"), "title\ntext\nitem more\nnext");
+    assert_eq!(text("> quoted\n> text\nplain"), "quoted text\nplain");
+}
+
+#[test]
+fn reads_commonmark_emphasis() {
+    let options = ParserOptions {
+        dialect: Dialect::CommonMark,
+        ..Default::default()
+    };
+    let items = Parser::with_options("_a_ __b__ ~~c~~ ~d $e^", options)
+        .filter_map(|item| match item {
+            Item::Text(style, text) if !text.trim().is_empty() => Some((style, text)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+    assert!(items[0].0.italics && !items[0].0.underline);
+    assert!(items[1].0.strong);
+    assert!(items[2].0.strikethrough);
+    assert!(items[3..].iter().all(|(style, _)| *style == Default::default()));
+    assert_eq!(items[3..].iter().map(|(_, text)| *text).collect::<String>(), "~d $e^");
+}
+
+#[test]
+fn ignores_delimiters_inside_words() {
+    let styled = |s| Parser::new(s).any(|item| matches!(item, Item::Text(style, _) if style != Default::default()));
+    assert!(!styled("my_var_name in /a_b/c_d"));
+    assert!(!styled("2*3*4 and 2**3**4"));
+    assert!(!styled("a~b~c, x^2^y, 1$2$3"));
+    assert!(styled("_under_ and *italic*"));
+
+    let strong = Parser::new("**bold_name** here")
+        .filter_map(|item| match item {
+            Item::Text(style, text) if style.strong && !style.underline => Some(text),
+            _ => None
+        })
+        .collect::<String>();
+    assert_eq!(strong, "bold_name");
+}
+
+#[test]
+fn strikes_through_with_one_or_two_tildes() {
+    let struck = |s| {
+        Parser::new(s)
+            .filter_map(|item| match item {
+                Item::Text(style, text) if style.strikethrough => Some(text),
+                _ => None
+            })
+            .collect::<String>()
+    };
+    assert_eq!(struck("~~gone~~ and ~old~"), "goneold");
+    assert_eq!(rendered("~~gone~~ and ~old~"), "gone and old");
+    assert_eq!(struck("a~~b~~c"), "");
+}
+
+#[test]
+fn resolves_reference_links() {
+    let s = "[Full][Docs], [docs][], [DOCS] and [none][missing] [x]\n\n[docs]: <https://example.com> \"Title\"\n  [other]: /a";
+    let mut parser = Parser::new(s);
+    let items = parser.by_ref().collect::<Vec<_>>();
+    let links = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Hyperlink(_, text, url) => Some((*text, *url)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(links, [("Full", "https://example.com"), ("docs", "https://example.com"), ("DOCS", "https://example.com")]);
+    assert!(items.contains(&Item::Text(Default::default(), "[none][missing]")));
+    assert!(items.contains(&Item::Todo(true)));
+    assert_eq!(parser.unresolved(), ["missing"]);
+    assert_eq!(items.iter().filter(|item| **item == Item::Newline).count(), 2);
+}
+
+#[test]
+fn links_bare_urls_and_emails() {
+    let links = |s| Parser::new(s)
+        .filter_map(|item| match item {
+            Item::Hyperlink(_, _, url) => Some(url),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        links("See https://example.com/a_b?q=1. Or (www.example.org), mail me.you@example.co.uk!"),
+        ["https://example.com/a_b?q=1", "www.example.org", "me.you@example.co.uk"]
+    );
+    assert_eq!(links("https://en.wikipedia.org/wiki/Rust_(language) file:///tmp/x"), [
+        "https://en.wikipedia.org/wiki/Rust_(language)",
+        "file:///tmp/x"
+    ]);
+    assert!(links("nothttps://a.com, www., a@b, @home").is_empty());
+
+    assert_eq!(link_target("www.example.org"), "https://www.example.org");
+    assert_eq!(link_target("me@example.com"), "mailto:me@example.com");
+    assert_eq!(link_target("https://example.com"), "https://example.com");
+}
+
+#[test]
+fn numbers_footnotes_by_first_reference() {
+    let s = "One[^b], two[^a] and again[^B]. Not[^none].\n[^a]: First *note*.\n[^b]: Second.";
+    let mut parser = Parser::new(s);
+    let items = parser.by_ref().collect::<Vec<_>>();
+    let footnotes = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Footnote(number, text) => Some((*number, *text)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(footnotes, [(1, "Second."), (2, "First *note*."), (1, "Second.")]);
+    assert!(matches!(items.last(), Some(Item::Newline)));
+    assert_eq!(parser.footnotes().collect::<Vec<_>>(), ["Second.", "First *note*."]);
+}
+
+#[test]
+fn reads_wiki_links() {
+    let items = Parser::new("See [[Other Note]], [[ notes/Plan | the plan]] and [[]] [[a\n]]").collect::<Vec<_>>();
+    let links = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::WikiLink(_, target, label) => Some((*target, *label)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(links, [("Other Note", "Other Note"), ("notes/Plan", "the plan")]);
+    assert_eq!(wiki_links("[[A]] [[B|b]]").collect::<Vec<_>>(), ["A", "B"]);
+}
+
+#[test]
+fn slugs_headings() {
+    assert_eq!(slug(" Getting Started: v2.0 (beta)! "), "getting-started-v20-beta");
+    assert_eq!(slug("Über_uns -- Team"), "über_uns----team");
+
+    let headings = headings("# Setup\ntext\n## Setup\n## *Install* it\n", Default::default());
+    let slugs = headings.iter().map(|heading| heading.slug.as_str()).collect::<Vec<_>>();
+    assert_eq!(slugs, ["setup", "setup-1", "install-it"]);
+    assert_eq!(headings[2].line, 3);
+}
+
+#[test]
+fn finds_lines_of_emoji_headings() {
+    // An emoji's glyph is not in the source, its shortcode places it.
+    let headings = headings("# A\n# :tada:\n# C", Default::default());
+    let lines = headings.iter().map(|heading| (heading.line, heading.text.as_str())).collect::<Vec<_>>();
+    assert_eq!(lines, [(0, "A"), (1, "🎉"), (2, "C")]);
+}
+
+#[test]
+fn reads_front_matter() {
+    let note = "---\ntitle: \"Groceries\"\ndate: 2024-05-01\ntags:\n  - home\n  - weekly\nauthor:\n  name: Sam\n---\n# List\n";
+    let front = front_matter::parse(note).unwrap();
+    assert_eq!(front.format, Format::Yaml);
+    assert_eq!(front.title(), Some("Groceries"));
+    assert_eq!(front.date(), Some("2024-05-01"));
+    assert_eq!(front.tags(), ["home", "weekly"]);
+    assert_eq!(front.others().count(), 0);
+    assert_eq!(rendered(note), "List\n");
+
+    let note = "+++\ntitle = 'Plan'\nkeywords = [\n  \"a\",\n  \"b\"\n]\ndraft = true\n[extra]\nx = 1\n+++\n";
+    let front = front_matter::parse(note).unwrap();
+    assert_eq!(front.format, Format::Toml);
+    assert_eq!(front.title(), Some("Plan"));
+    assert_eq!(front.tags(), ["a", "b"]);
+    assert_eq!(front.fields.last(), Some(&("draft", Value::Text("true"))));
+
+    // Separators around ordinary text are not front matter.
+    assert!(front_matter::parse("---\nJust a note\n---\n").is_none());
+    assert!(front_matter::parse("---\ntitle: never closed\n").is_none());
+}
+
+#[test]
+fn reads_callouts() {
+    let items = Parser::new("> [!WARNING]- Mind the gap\n> Body\n").collect::<Vec<_>>();
+    assert_eq!(items[..2], [Item::QuoteIndent, Item::Callout(CalloutKind::Warning, Some(false), "Mind the gap")]);
+    assert_eq!(rendered("> [!note]\n> Body\n"), "\nBody\n");
+
+    // Only the first line of a quote opens a callout, and unknown kinds
+    // stay text.
+    let callouts = |s| Parser::new(s).filter(|item| matches!(item, Item::Callout(..))).count();
+    assert_eq!(callouts("> text\n> [!TIP]\n"), 0);
+    assert_eq!(callouts("> text\n> > [!TIP]\n"), 1);
+    assert_eq!(callouts("> [!UNKNOWN]\n"), 0);
+    assert_eq!(callouts("[!NOTE]\n"), 0);
+
+    // Paragraph mode keeps the title on its own line.
+    let options = ParserOptions { paragraphs: true, ..Default::default() };
+    let lines = Parser::with_options("> [!TIP]\n> one\n> two\n", options)
+        .filter(|item| *item == Item::Newline)
+        .count();
+    assert_eq!(lines, 2);
+}
+
+#[test]
+fn reads_math_when_enabled() {
+    let options = ParserOptions { math: true, ..Default::default() };
+    let math = |s| {
+        Parser::with_options(s, options)
+            .filter_map(|item| match item {
+                Item::Math(tex, display) => Some((tex, display)),
+                _ => None
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(math("so $x^2$ and\n$$\n\\frac{a}{b}\n$$\n"), [("x^2", false), ("\n\\frac{a}{b}\n", true)]);
+    assert_eq!(math("\\(a\\) \\[b\\]"), [("a", false), ("b", true)]);
+    assert_eq!(math("costs $5 or $10, $ x $"), []);
+    assert_eq!(rendered("$small$"), "small");
+}
+
+#[test]
+fn reads_emoji_shortcodes() {
+    let emoji = |s| {
+        Parser::new(s)
+            .filter_map(|item| match item {
+                Item::Emoji(_, emoji, shortcode) => Some((emoji, shortcode)),
+                _ => None
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(emoji("Shipped :tada: today"), [("🎉", "tada")]);
+    assert_eq!(emoji("*:+1:*"), [("👍", "+1")]);
+    assert_eq!(emoji("at 10:30:45"), []);
+    assert_eq!(emoji("a:tada: and :notanemoji:"), []);
+    assert_eq!(rendered("`:tada:`"), ":tada:");
+}
+
+#[test]
+fn reads_definition_lists() {
+    let items = Parser::new("Term\n: One\n: Two\n").collect::<Vec<_>>();
+    let strong = |item: &Item| matches!(item, Item::Text(style, _) if style.strong);
+    assert!(strong(&items[0]));
+    assert_eq!(items.iter().filter(|item| **item == Item::Definition).count(), 2);
+    assert_eq!(rendered("Term\n: One\n"), "Term\nOne\n");
+
+    // A definition needs a term right above it.
+    let definitions = |s| Parser::new(s).filter(|item| *item == Item::Definition).count();
+    assert_eq!(definitions(": alone\n"), 0);
+    assert_eq!(definitions("Term\n\n: apart\n"), 0);
+
+    // Paragraph mode keeps the term and each definition on their own lines.
+    let options = ParserOptions { paragraphs: true, ..Default::default() };
+    let lines = Parser::with_options("Term\n: one\n: two\n", options)
+        .filter(|item| *item == Item::Newline)
+        .count();
+    assert_eq!(lines, 3);
+}
+
+#[test]
+fn reads_abbreviations() {
+    let s = "The HTML spec, not HTMLX.\n\n*[HTML]: Hyper Text Markup Language\n";
+    let items = Parser::new(s).collect::<Vec<_>>();
+    assert!(items.iter().any(|item| matches!(item, Item::Abbreviation(_, "HTML", "Hyper Text Markup Language"))));
+    assert_eq!(items.iter().filter(|item| matches!(item, Item::Abbreviation(..))).count(), 1);
+    assert_eq!(rendered(s), "The HTML spec, not HTMLX.\n\n");
+    assert_eq!(rendered("*[W3C]: World Wide Web Consortium\n```\nW3C\n```"), "W3C");
+}
+
+#[test]
+fn reads_extended_inline_styles() {
+    let styles = |s, options| {
+        Parser::with_options(s, options)
+            .filter_map(|item| match item {
+                Item::Text(style, text) => Some((text, style)),
+                _ => None
+            })
+            .collect::<Vec<_>>()
+    };
+    let marko = ParserOptions::default();
+    let commonmark = ParserOptions { dialect: Dialect::CommonMark, ..Default::default() };
+    let plain = Style::default();
+    let highlight = Style { highlight: true, ..plain };
+    let lowered = Style { lowered: true, ..plain };
+    let keyboard = Style { keyboard: true, ..plain };
+
+    assert_eq!(styles("a ==b== c", marko), [("a ", plain), ("b", highlight), (" c", plain)]);
+    assert_eq!(styles("H<sub>2</sub>O", marko), [("H", plain), ("2", lowered), ("O", plain)]);
+    assert_eq!(styles("<mark>x</mark>", commonmark), [("x", highlight)]);
+    assert_eq!(styles("<kbd>Ctrl</kbd>", marko), [("Ctrl", keyboard)]);
+    let red = Style { color: Some([220, 50, 47]), ..plain };
+    assert_eq!(styles("<span style=\"color: red\">hot</span>", marko), [("hot", red)]);
+
+    // Equals signs inside words and unknown colors are left alone.
+    assert_eq!(rendered("a==b"), "a==b");
+    assert_eq!(styles("<span style=\"color: nope\">x</span>", marko).last(), Some(&("x", plain)));
+}
+
+#[test]
+fn reads_colors() {
+    assert_eq!(color("#f80"), Some([255, 136, 0]));
+    assert_eq!(color("#102030"), Some([16, 32, 48]));
+    assert_eq!(color("Blue"), Some([38, 110, 220]));
+    assert_eq!(color("#12"), None);
+    assert_eq!(color("#ggg"), None);
+}
+
+/// `@name` mentions inline, and `%%` comment lines that render nothing.
+struct Mentions;
+
+impl Extension for Mentions {
+    fn block<'a>(&self, s: &'a str) -> Option<(usize, Item<'a>)> {
+        s.starts_with("%%").then(|| (s.find('\n').unwrap_or(s.len()), Item::Custom(Style::default(), "comment", "")))
+    }
+
+    fn inline<'a>(&self, s: &'a str, style: Style) -> Option<(usize, Item<'a>)> {
+        let name = s.strip_prefix('@')?;
+        let len = name.find(|c: char| !c.is_alphanumeric()).unwrap_or(name.len());
+        (len > 0).then(|| (len + 1, Item::Custom(style, "mention", &name[..len])))
+    }
+
+    fn triggers(&self) -> &[char] {
+        &['@']
+    }
+}
+
+#[test]
+fn reads_extension_syntax() {
+    let extensions: Vec<Box<dyn Extension>> = vec![Box::new(Mentions)];
+    let custom = |s| {
+        Parser::new(s)
+            .with_extensions(&extensions)
+            .filter_map(|item| match item {
+                Item::Custom(style, kind, text) => Some((kind, text, style.strong)),
+                _ => None
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(custom("Ask **@ana** or @bo"), [("mention", "ana", true), ("mention", "bo", false)]);
+    assert_eq!(custom("%% hidden\ntext @ and 漢@b"), [("comment", "", false), ("mention", "b", false)]);
+    assert_eq!(custom("`@code`"), []);
+    assert_eq!(rendered("Ask @ana"), "Ask @ana");
+    assert_eq!(custom("> %% hidden\n> > @ana"), [("comment", "", false), ("mention", "ana", false)]);
+}
+
+/// Remembers every line its `block` is offered.
+struct Lines(Arc<Mutex<Vec<String>>>);
+
+impl Extension for Lines {
+    fn block<'a>(&self, s: &'a str) -> Option<(usize, Item<'a>)> {
+        let line = s.split('\n').next().unwrap_or_default();
+        self.0.lock().unwrap().push(line.to_string());
+        None
+    }
+}
+
+#[test]
+fn offers_blocks_past_quote_markers() {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let extensions: Vec<Box<dyn Extension>> = vec![Box::new(Lines(Arc::clone(&lines)))];
+    for _ in Parser::new("> a\n> > b\n>   c\nd").with_extensions(&extensions) {}
+    assert_eq!(*lines.lock().unwrap(), ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn parses_long_lines_in_linear_time() {
+    // Every `:` and `/` ends a text run, the parser must not search the
+    // rest of the line for links again at each of them.
+    let line = "plain words: and/or more ".repeat(20_000);
+    let start = std::time::Instant::now();
+    assert_eq!(rendered(&line), line);
+    assert!(start.elapsed() < std::time::Duration::from_secs(2), "took {:?}", start.elapsed());
+}
@@ -0,0 +1,69 @@
+use std::{fs, path::PathBuf};
+use mk_core::recovery::{list, lock, new_id, remove, store};
+
+/// An empty directory of its own for each test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("marko-recovery-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn stores_and_lists_buffers() {
+    let dir = scratch("round-trip");
+    assert!(list(&dir).unwrap().is_empty());
+
+    store(&dir, "1-0", Some(&PathBuf::from("/notes/a.md")), "# A\nbody").unwrap();
+    store(&dir, "1-1", None, "").unwrap();
+    let mut recovered = list(&dir).unwrap();
+    recovered.sort_by(|a, b| a.id.cmp(&b.id));
+    assert_eq!(recovered.len(), 2);
+    assert_eq!(recovered[0].original, Some(PathBuf::from("/notes/a.md")));
+    assert_eq!(recovered[0].code, "# A\nbody");
+    assert_eq!(recovered[1].original, None);
+    assert!(!recovered[0].clean);
+
+    // Storing again replaces the buffer, removing it twice is fine.
+    store(&dir, "1-0", None, "newer").unwrap();
+    remove(&dir, "1-1").unwrap();
+    remove(&dir, "1-1").unwrap();
+    let recovered = list(&dir).unwrap();
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0].code, "newer");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn skips_buffers_of_running_instances() {
+    let dir = scratch("running");
+    let id = new_id();
+    let running = lock(&dir).unwrap();
+    store(&dir, &id, None, "open elsewhere").unwrap();
+    assert!(list(&dir).unwrap().is_empty());
+
+    // Dropping the lock without releasing it is a crash.
+    drop(running);
+    let recovered = list(&dir).unwrap();
+    assert_eq!(recovered.len(), 1);
+    assert!(!recovered[0].clean);
+
+    lock(&dir).unwrap().release().unwrap();
+    let recovered = list(&dir).unwrap();
+    assert!(recovered[0].clean);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn locking_clears_locks_of_finished_instances() {
+    let dir = scratch("stale");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("4242.lock"), "clean").unwrap();
+    fs::write(dir.join("4343.lock"), "").unwrap();
+    store(&dir, "4343-0", None, "left behind").unwrap();
+
+    let _lock = lock(&dir).unwrap();
+    assert!(!dir.join("4242.lock").exists());
+    assert!(dir.join("4343.lock").exists());
+    assert_eq!(list(&dir).unwrap().len(), 1);
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,100 @@
+use std::{fs, path::PathBuf, thread, time::Duration};
+use mk_core::search::{Index, Query};
+
+/// An empty directory of its own for each test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("marko-search-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn wait(index: &Index) {
+    while index.is_indexing() {
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn query(text: &str) -> Query {
+    Query {
+        text: text.to_string(),
+        ..Query::default()
+    }
+}
+
+#[test]
+fn finds_lines_in_every_file() {
+    let dir = scratch("lines");
+    let a = dir.join("a.md");
+    let b = dir.join("b.md");
+    fs::write(&a, "# Apples\nGreen apple\nnothing").unwrap();
+    fs::write(&b, "pear\nAPPLE pie, apple tart").unwrap();
+    let index = Index::new([a.clone(), b.clone()], || {});
+    wait(&index);
+
+    let results = index.search(&query("apple")).unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].path, a);
+    let lines = results[0].lines.iter().map(|line| line.line).collect::<Vec<_>>();
+    assert_eq!(lines, vec![0, 1]);
+    assert_eq!(results[1].lines[0].ranges, vec![0..5, 11..16]);
+
+    let sensitive = Query {
+        case_sensitive: true,
+        ..query("APPLE")
+    };
+    assert_eq!(index.search(&sensitive).unwrap()[0].path, b);
+    let regex = Query {
+        regex: true,
+        ..query("^p")
+    };
+    assert_eq!(index.search(&regex).unwrap().len(), 1);
+    assert!(index.search(&Query { regex: true, ..query("(") }).is_err());
+    assert!(index.search(&query("")).unwrap().is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn follows_changes_on_disk() {
+    let dir = scratch("update");
+    let a = dir.join("a.md");
+    fs::write(&a, "old").unwrap();
+    let index = Index::new([a.clone()], || {});
+    wait(&index);
+    let generation = index.generation();
+
+    fs::write(&a, "new").unwrap();
+    index.update(a.clone());
+    wait(&index);
+    assert!(index.generation() > generation);
+    assert!(index.search(&query("old")).unwrap().is_empty());
+    assert_eq!(index.search(&query("new")).unwrap().len(), 1);
+
+    fs::remove_file(&a).unwrap();
+    index.update(a);
+    wait(&index);
+    assert!(index.search(&query("new")).unwrap().is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn lists_notes_linking_to_a_note() {
+    let dir = scratch("backlinks");
+    let todo = dir.join("Todo.md");
+    let daily = dir.join("daily.md");
+    let other = dir.join("other.md");
+    fs::write(&todo, "[[todo]] links to itself").unwrap();
+    fs::write(&daily, "- [ ] see [[notes/TODO.md]]").unwrap();
+    fs::write(&other, "`[[todo]]` is code, [[todos]] another note").unwrap();
+    let index = Index::new([todo.clone(), daily.clone(), other.clone()], || {});
+    wait(&index);
+
+    assert_eq!(index.backlinks(&todo), vec![daily.clone()]);
+    assert!(index.backlinks(&daily).is_empty());
+
+    fs::write(&other, "[[Todo]]").unwrap();
+    index.update(other.clone());
+    wait(&index);
+    assert_eq!(index.backlinks(&todo), vec![daily, other]);
+    fs::remove_dir_all(&dir).unwrap();
+}
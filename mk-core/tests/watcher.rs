@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant}
+};
+use mk_core::watcher::Watcher;
+
+/// An empty directory of its own for each test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("marko-watcher-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir.canonicalize().unwrap()
+}
+
+/// Waits a little for `path` to be reported.
+fn sees_change(watcher: &Watcher, path: &Path) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if watcher.changed().iter().any(|changed| changed == path) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn reports_changes_to_watched_files() {
+    let dir = scratch("file");
+    let note = dir.join("note.md");
+    let other = dir.join("other.md");
+    fs::write(&note, "one").unwrap();
+    fs::write(&other, "one").unwrap();
+
+    let mut watcher = Watcher::new(|| {}).unwrap();
+    watcher.watch(&note).unwrap();
+    fs::write(&other, "two").unwrap();
+    fs::write(&note, "two").unwrap();
+    assert!(sees_change(&watcher, &note));
+    assert!(!watcher.changed().contains(&other));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn keeps_watching_open_files_after_leaving_a_folder() {
+    let dir = scratch("tree");
+    let note = dir.join("note.md");
+    fs::write(&note, "one").unwrap();
+
+    let mut watcher = Watcher::new(|| {}).unwrap();
+    watcher.watch_tree(&dir).unwrap();
+    watcher.watch(&note).unwrap();
+    watcher.unwatch_tree(&dir).unwrap();
+    fs::write(&note, "two").unwrap();
+    assert!(sees_change(&watcher, &note));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn hands_subfolders_over_to_a_watched_tree_and_back() {
+    let dir = scratch("subfolder");
+    fs::create_dir(dir.join("sub")).unwrap();
+    let note = dir.join("sub").join("note.md");
+    fs::write(&note, "one").unwrap();
+
+    let mut watcher = Watcher::new(|| {}).unwrap();
+    watcher.watch(&note).unwrap();
+    watcher.watch_tree(&dir).unwrap();
+    fs::write(&note, "two").unwrap();
+    assert!(sees_change(&watcher, &note));
+
+    watcher.unwatch_tree(&dir).unwrap();
+    fs::write(&note, "three").unwrap();
+    assert!(sees_change(&watcher, &note));
+    fs::remove_dir_all(dir).unwrap();
+}
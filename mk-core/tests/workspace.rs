@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+use mk_core::workspace::{links_to, new_note_path, note_link};
+
+#[test]
+fn reads_links_to_local_notes() {
+    assert_eq!(note_link("../notes/todo.md#today"), Some((PathBuf::from("../notes/todo.md"), Some("today"))));
+    assert_eq!(note_link("file:///home/me/my%20plan.md"), Some((PathBuf::from("/home/me/my plan.md"), None)));
+    assert_eq!(note_link("notes/image.png"), None);
+    assert_eq!(note_link("https://example.com/readme.md"), None);
+}
+
+#[test]
+fn leaves_hosts_and_email_addresses_to_the_browser() {
+    assert_eq!(note_link("www.gov.md"), None);
+    assert_eq!(note_link("WWW.example.md/a.md"), None);
+    assert_eq!(note_link("a@b.md"), None);
+    assert!(note_link("notes/www.md").is_some());
+}
+
+#[test]
+fn matches_wiki_link_targets_to_notes() {
+    let path = Path::new("/notes/Todo.md");
+    assert!(links_to("todo", path));
+    assert!(links_to(" archive/TODO.md ", path));
+    assert!(!links_to("todos", path));
+    assert!(!links_to("todo", Path::new("/notes/todo.txt")));
+}
+
+#[test]
+fn keeps_new_notes_inside_their_folder() {
+    let dir = Path::new("/notes");
+    assert_eq!(new_note_path(dir, " Plan "), Some(PathBuf::from("/notes/Plan.md")));
+    assert_eq!(new_note_path(dir, "Plan.md"), Some(PathBuf::from("/notes/Plan.md")));
+    assert_eq!(new_note_path(dir, "sub/new"), Some(PathBuf::from("/notes/sub/new.md")));
+    assert_eq!(new_note_path(dir, "../../x"), None);
+    assert_eq!(new_note_path(dir, "sub/../../x"), None);
+    assert_eq!(new_note_path(dir, "/etc/x"), None);
+    assert_eq!(new_note_path(dir, ""), None);
+}
@@ -1,17 +1,28 @@
+use std::path::PathBuf;
 use mk_core::eframe::{
-    self, App, Frame,
+    App, CreationContext, Frame,
     egui::{ Context }
 };
-use super::app_state::UIState;
+use mk_core::recovery::{self, Lock};
+use super::{app_state::UIState, fonts};
 
+#[derive(Default)]
 pub struct MarkoApp {
-    state: UIState
+    state: UIState,
+    /// Keeps other instances from offering to recover our open buffers.
+    lock: Option<Lock>
 }
 
-impl Default for MarkoApp {
-    fn default() -> Self {
+impl MarkoApp {
+    pub fn new(cc: &CreationContext, path: Option<PathBuf>) -> Self {
+        fonts::install(&cc.egui_ctx);
+        // Look for buffers to recover before taking our own lock, which
+        // clears out the locks of instances that are gone.
+        let state = UIState::new(path);
+        let lock = recovery::recovery_dir().and_then(|dir| recovery::lock(&dir).ok());
         MarkoApp {
-            state: UIState::default()
+            state,
+            lock
         }
     }
 }
@@ -20,4 +31,12 @@ impl App for MarkoApp {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
         self.state.update(ctx);
     }
+
+    fn on_exit(&mut self, _gl: Option<&mk_core::eframe::glow::Context>) {
+        self.state.on_exit();
+        if let Some(lock) = self.lock.take() {
+            // Marks this instance clean, so the next launch only offers what a crash left.
+            let _ = lock.release();
+        }
+    }
 }
@@ -1,23 +1,104 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant}
+};
 use mk_core::eframe::egui::{
-    self, Response, Context, Ui,
-    ScrollArea
+    self, Context, Ui, Key,
+    ScrollArea, ViewportCommand
+};
+use mk_core::parser::Dialect;
+use mk_core::recovery::{self, Recovered};
+use mk_core::watcher::Watcher;
+use mk_core::search::Index;
+use mk_core::workspace::{self, Workspace};
+use super::super::widgets::{
+    diff, viewer::{self, Link}, Backlinks, Command,
+    CommandPalette, Editor, FileTree, QuickOpen,
+    SearchPanel, TreeAction
 };
-use super::super::widgets::Editor;
 use super::{UIAction, UIStateVariant};
 
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_RECENT: usize = 20;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Sidebar {
+    Files,
+    Search,
+    Backlinks
+}
+
+enum Prompt {
+    OpenFile(String),
+    OpenFolder(String),
+    /// The path typed so far, and the id of the tab to close once it is
+    /// saved, when saving is part of closing it.
+    SaveAs(String, Option<String>)
+}
+
 pub struct MainUI {
-    editor: Editor,
+    tabs: Vec<Editor>,
+    active: usize,
+    workspace: Option<Workspace>,
+    file_tree: FileTree,
+    search_index: Option<Index>,
+    search: SearchPanel,
+    backlinks: Backlinks,
+    sidebar: Sidebar,
+    quick_open: Option<QuickOpen>,
+    command_palette: Option<CommandPalette>,
+    recent: Vec<PathBuf>,
+    /// Notes left by following links, most recent last.
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
     show_source: bool,
     show_rendered: bool,
+    show_sidebar: bool,
+    recovery_dir: Option<PathBuf>,
+    last_autosave: Instant,
+    watcher: Option<Watcher>,
+    watched: HashSet<PathBuf>,
+    watched_tree: Option<PathBuf>,
+    show_conflict_diff: bool,
+    prompt: Option<Prompt>,
+    /// Id of the tab asking whether to save before closing.
+    closing: Option<String>,
+    title: String,
+    status: Option<String>,
     action: Option<UIAction>
 }
 
 impl Default for MainUI {
     fn default() -> Self {
         MainUI {
-            editor: Default::default(),
+            tabs: vec![Editor::default()],
+            active: 0,
+            workspace: None,
+            file_tree: FileTree::default(),
+            search_index: None,
+            search: SearchPanel::default(),
+            backlinks: Backlinks::default(),
+            sidebar: Sidebar::Files,
+            quick_open: None,
+            command_palette: None,
+            recent: Vec::new(),
+            back: Vec::new(),
+            forward: Vec::new(),
             show_source: true,
             show_rendered: true,
+            show_sidebar: true,
+            recovery_dir: recovery::recovery_dir(),
+            last_autosave: Instant::now(),
+            watcher: None,
+            watched: HashSet::new(),
+            watched_tree: None,
+            show_conflict_diff: false,
+            prompt: None,
+            closing: None,
+            title: String::new(),
+            status: None,
             action: None
         }
     }
@@ -25,38 +106,919 @@ impl Default for MainUI {
 
 impl UIStateVariant for MainUI {
     fn update_panel(&mut self, ctx: &Context) -> UIAction {
+        self.shortcuts(ctx);
+        self.quick_open_window(ctx);
+        self.command_palette_window(ctx);
+        self.prompt_window(ctx);
+        self.close_window(ctx);
+        self.autosave(ctx);
+        self.watch(ctx);
+
+        let editor = &self.tabs[self.active];
+        let title = format!(
+            "{}{} - Marko",
+            if editor.is_dirty() { "*" } else { "" },
+            editor.title()
+        );
+        if title != self.title {
+            ctx.send_viewport_cmd(ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+
+        egui::TopBottomPanel::top("menu")
+            .show(ctx, |ui| self.menu_bar(ui));
+        self.conflict_banner(ctx);
+
+        if let Some(status) = &self.status {
+            let mut dismissed = false;
+            egui::TopBottomPanel::bottom("status")
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(status);
+                        dismissed = ui.small_button("✖").clicked();
+                    });
+                });
+            if dismissed {
+                self.status = None;
+            }
+        }
+
+        self.sidebar(ctx);
+
         egui::CentralPanel::default()
             .show(ctx, |ui| {
+                self.tab_bar(ui);
                 self.ui(ui);
             });
 
+        self.remember_recent();
         self.action.take().unwrap_or(UIAction::Nothing)
     }
 }
 
 impl MainUI {
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let mut main = MainUI::default();
+        match path {
+            Some(path) if path.is_dir() => main.open_folder(path),
+            Some(path) => main.open_file(path),
+            None => {}
+        }
+        main
+    }
+
+    pub fn restore(recovered: Vec<Recovered>, launch: Option<PathBuf>) -> Self {
+        let mut main = MainUI::open(launch);
+        for recovered in recovered {
+            let id = recovered.id.clone();
+            let mut editor = Editor::recovered(recovered);
+            // Move the buffer over to this instance's id, so other instances
+            // see it is open here.
+            if let Some(dir) = &main.recovery_dir {
+                if let Err(error) = editor.autosave(dir).and_then(|()| recovery::remove(dir, &id)) {
+                    main.status = Some(format!("Could not move recovered buffer: {error}"));
+                }
+            }
+            main.push_tab(editor);
+        }
+        main
+    }
+
+    fn editor(&mut self) -> &mut Editor {
+        &mut self.tabs[self.active]
+    }
+
+    fn tab_index(&self, id: &str) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.id() == id)
+    }
+
+    /// Adds a tab and focuses it, replacing the initial empty buffer.
+    fn push_tab(&mut self, editor: Editor) {
+        if let [only] = self.tabs.as_slice() {
+            if only.path().is_none() && !only.is_dirty() {
+                self.tabs.clear();
+            }
+        }
+        self.tabs.push(editor);
+        self.active = self.tabs.len() - 1;
+    }
+
+    fn open_file(&mut self, path: PathBuf) {
+        if let Some(index) = self.tabs.iter().position(|tab| same_file(tab.path(), &path)) {
+            self.active = index;
+            return;
+        }
+
+        match Editor::open(path.clone()) {
+            Ok(editor) => self.push_tab(editor),
+            Err(error) => {
+                self.status = Some(format!("Could not open {}: {error}", path.display()));
+            }
+        }
+    }
+
+    fn open_folder(&mut self, path: PathBuf) {
+        match Workspace::open(path.clone()) {
+            Ok(workspace) => {
+                self.workspace = Some(workspace);
+                self.search_index = None;
+                self.show_sidebar = true;
+            }
+            Err(error) => {
+                self.status = Some(format!("Could not open {}: {error}", path.display()));
+            }
+        }
+    }
+
+    fn close_tab(&mut self, index: usize) {
+        if self.tabs[index].is_dirty() {
+            self.closing = Some(self.tabs[index].id().to_string());
+            return;
+        }
+        self.remove_tab(index);
+    }
+
+    fn remove_tab(&mut self, index: usize) {
+        let mut editor = self.tabs.remove(index);
+        if let Some(dir) = &self.recovery_dir {
+            if let Err(error) = editor.discard_recovery(dir) {
+                self.status = Some(format!("Could not remove recovery file: {error}"));
+            }
+        }
+
+        if self.tabs.is_empty() {
+            self.tabs.push(Editor::default());
+        }
+        if self.active > index || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+    }
+
+    pub fn perform(&mut self, action: UIAction) {
+        if !self.is_enabled(&action) {
+            return;
+        }
+
+        match action {
+            UIAction::NewTab => self.push_tab(Editor::default()),
+            UIAction::OpenFile => self.prompt = Some(Prompt::OpenFile(String::new())),
+            UIAction::OpenFolder => self.prompt = Some(Prompt::OpenFolder(String::new())),
+            UIAction::Save => self.save(),
+            UIAction::SaveAs => self.prompt = Some(Prompt::SaveAs(String::new(), None)),
+            UIAction::CloseTab => self.close_tab(self.active),
+            UIAction::QuickOpen => self.show_quick_open(),
+            UIAction::Search => self.show_search(),
+            UIAction::CommandPalette => self.command_palette = Some(CommandPalette::default()),
+            UIAction::ToggleSidebar => self.show_sidebar ^= true,
+            UIAction::ToggleSource => self.show_source ^= true,
+            UIAction::ToggleRendered => self.show_rendered ^= true,
+            UIAction::ToggleParagraphs => self.tabs[self.active].options.paragraphs ^= true,
+            UIAction::ToggleMath => self.tabs[self.active].options.math ^= true,
+            UIAction::ToggleDialect => {
+                let dialect = &mut self.tabs[self.active].options.dialect;
+                *dialect = match dialect {
+                    Dialect::Marko => Dialect::CommonMark,
+                    Dialect::CommonMark => Dialect::Marko
+                };
+            }
+            UIAction::Back => {
+                if let Some(path) = self.back.pop() {
+                    self.step(path, true);
+                }
+            }
+            UIAction::Forward => {
+                if let Some(path) = self.forward.pop() {
+                    self.step(path, false);
+                }
+            }
+            action => self.action = Some(action)
+        }
+    }
+
+    fn is_enabled(&self, action: &UIAction) -> bool {
+        match action {
+            UIAction::QuickOpen | UIAction::Search | UIAction::ToggleSidebar => self.workspace.is_some(),
+            UIAction::Back => !self.back.is_empty(),
+            UIAction::Forward => !self.forward.is_empty(),
+            _ => true
+        }
+    }
+
+    fn shortcuts(&mut self, ctx: &Context) {
+        for action in UIAction::COMMANDS {
+            let Some(shortcut) = action.shortcut() else {
+                continue;
+            };
+            if ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+                self.perform(action);
+            }
+        }
+    }
+
+    fn command_palette_window(&mut self, ctx: &Context) {
+        if self.command_palette.is_none() {
+            return;
+        }
+
+        let commands = UIAction::COMMANDS
+            .iter()
+            .map(|action| Command {
+                name: action.name(),
+                shortcut: action.shortcut(),
+                enabled: self.is_enabled(action)
+            })
+            .collect::<Vec<_>>();
+
+        let mut open = true;
+        let picked = self.command_palette
+            .as_mut()
+            .and_then(|palette| palette.show(ctx, &commands, &mut open));
+        if !open {
+            self.command_palette = None;
+        }
+        if let Some(index) = picked {
+            self.perform(UIAction::COMMANDS[index].clone());
+        }
+    }
+
+    fn command_button(&mut self, ui: &mut Ui, action: UIAction) {
+        let mut button = egui::Button::new(action.name());
+        if let Some(shortcut) = action.shortcut() {
+            button = button.shortcut_text(ui.ctx().format_shortcut(&shortcut));
+        }
+        if ui.add_enabled(self.is_enabled(&action), button).clicked() {
+            self.perform(action);
+            ui.close_menu();
+        }
+    }
+
+    fn show_search(&mut self) {
+        if self.workspace.is_none() {
+            self.status = Some("Open a folder to search across its notes".to_string());
+            return;
+        }
+        self.show_sidebar = true;
+        self.sidebar = Sidebar::Search;
+        self.search.focus();
+    }
+
+    fn show_quick_open(&mut self) {
+        let Some(workspace) = &self.workspace else {
+            self.status = Some("Open a folder to search it with quick open".to_string());
+            return;
+        };
+        self.quick_open = Some(QuickOpen::new(workspace.root(), &workspace.files()));
+    }
+
+    fn quick_open_window(&mut self, ctx: &Context) {
+        if self.quick_open.is_none() {
+            return;
+        }
+        self.ensure_index(ctx);
+        let (Some(quick_open), Some(index)) = (&mut self.quick_open, &self.search_index) else {
+            return;
+        };
+
+        let mut open = true;
+        let selection = quick_open.show(ctx, index, &self.recent, &mut open);
+        if !open {
+            self.quick_open = None;
+        }
+
+        if let Some(selection) = selection {
+            self.open_file(selection.path);
+            if let Some(line) = selection.line {
+                self.editor().goto_line(line);
+            }
+        }
+    }
+
+    /// Keeps `recent` ordered by when each document was last active.
+    fn remember_recent(&mut self) {
+        let Some(path) = self.tabs[self.active].canonical_path().map(Path::to_path_buf) else {
+            return;
+        };
+        if self.recent.first() == Some(&path) {
+            return;
+        }
+        self.recent.retain(|recent| *recent != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    fn menu_bar(&mut self, ui: &mut Ui) {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                self.command_button(ui, UIAction::NewTab);
+                self.command_button(ui, UIAction::OpenFile);
+                self.command_button(ui, UIAction::OpenFolder);
+                self.command_button(ui, UIAction::QuickOpen);
+                self.command_button(ui, UIAction::Search);
+                ui.separator();
+                self.command_button(ui, UIAction::Save);
+                self.command_button(ui, UIAction::SaveAs);
+                ui.separator();
+                self.command_button(ui, UIAction::CloseTab);
+            });
+
+            ui.menu_button("View", |ui| {
+                ui.add_enabled(
+                    self.workspace.is_some(),
+                    egui::Checkbox::new(&mut self.show_sidebar, "Sidebar")
+                );
+                ui.checkbox(&mut self.show_source, "Source");
+                ui.checkbox(&mut self.show_rendered, "Rendered");
+                ui.checkbox(&mut self.tabs[self.active].options.paragraphs, "Paragraphs")
+                    .on_hover_text("Join lines into paragraphs in this document");
+                ui.checkbox(&mut self.tabs[self.active].options.math, "Math")
+                    .on_hover_text("Read $..$ and $$..$$ as TeX math in this document");
+                ui.menu_button("Dialect", |ui| {
+                    let dialect = &mut self.tabs[self.active].options.dialect;
+                    ui.radio_value(dialect, Dialect::Marko, "Marko");
+                    ui.radio_value(dialect, Dialect::CommonMark, "CommonMark");
+                });
+                ui.separator();
+                self.command_button(ui, UIAction::CommandPalette);
+            });
+
+            ui.menu_button("Go", |ui| {
+                self.command_button(ui, UIAction::Back);
+                self.command_button(ui, UIAction::Forward);
+            });
+        });
+    }
+
+    fn tab_bar(&mut self, ui: &mut Ui) {
+        let mut closed = None;
+        ui.horizontal_wrapped(|ui| {
+            for (index, tab) in self.tabs.iter().enumerate() {
+                let label = if tab.is_dirty() {
+                    format!("*{}", tab.title())
+                } else {
+                    tab.title()
+                };
+                let mut response = ui.selectable_label(index == self.active, label);
+                if let Some(path) = tab.path() {
+                    response = response.on_hover_text(path.display().to_string());
+                }
+                if response.clicked() {
+                    self.active = index;
+                }
+                if response.middle_clicked() || ui.small_button("✖").clicked() {
+                    closed = Some(index);
+                }
+                ui.add_space(8.0);
+            }
+        });
+        ui.separator();
+
+        if let Some(index) = closed {
+            self.close_tab(index);
+        }
+    }
+
+    /// Starts indexing the workspace the first time something needs it.
+    fn ensure_index(&mut self, ctx: &Context) {
+        let Some(workspace) = &self.workspace else {
+            return;
+        };
+        self.search_index.get_or_insert_with(|| {
+            let ctx = ctx.clone();
+            let files = workspace
+                .files()
+                .into_iter()
+                .filter(|path| workspace::is_markdown(path))
+                .map(Path::to_path_buf);
+            Index::new(files, move || ctx.request_repaint())
+        });
+    }
+
+    fn sidebar(&mut self, ctx: &Context) {
+        if !self.show_sidebar {
+            return;
+        }
+        self.ensure_index(ctx);
+        let (Some(workspace), Some(index)) = (&self.workspace, &self.search_index) else {
+            return;
+        };
+
+        let open = self.tabs[self.active].canonical_path();
+        let mut action = None;
+        let mut found = None;
+        let mut backlink = None;
+        egui::SidePanel::left("sidebar")
+            .resizable(true)
+            .default_width(200.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.sidebar, Sidebar::Files, "Files");
+                    ui.selectable_value(&mut self.sidebar, Sidebar::Search, "Search");
+                    ui.selectable_value(&mut self.sidebar, Sidebar::Backlinks, "Backlinks");
+                });
+                ui.separator();
+                match self.sidebar {
+                    Sidebar::Files => action = self.file_tree.ui(ui, workspace, open),
+                    Sidebar::Search => found = self.search.ui(ui, index, workspace.root()),
+                    Sidebar::Backlinks => {
+                        backlink = self.backlinks.ui(ui, index, workspace.root(), open);
+                    }
+                }
+            });
+
+        if let Some(action) = action {
+            self.tree_action(action);
+        }
+        if let Some((path, line)) = found {
+            self.open_file(path);
+            self.editor().goto_line(line);
+        }
+        if let Some(path) = backlink {
+            self.open_file(path);
+        }
+    }
+
+    fn tree_action(&mut self, action: TreeAction) {
+        let moved_or_deleted = matches!(action, TreeAction::Rename(..) | TreeAction::Delete(_));
+        let result = match action {
+            TreeAction::Open(path) => {
+                self.open_file(path);
+                Ok(())
+            }
+            TreeAction::CreateFile(path) => workspace::create_file(&path).map(|()| self.open_file(path)),
+            TreeAction::CreateDir(path) => workspace::create_dir(&path),
+            TreeAction::Rename(from, to) => workspace::rename(&from, &to).map(|()| {
+                for tab in &mut self.tabs {
+                    let moved = tab.canonical_path()
+                        .or(tab.path())
+                        .and_then(|path| path.strip_prefix(&from).ok().map(|rest| to.join(rest)));
+                    if let Some(moved) = moved {
+                        tab.set_path(moved);
+                    }
+                }
+            }),
+            TreeAction::Delete(path) => workspace::delete(&path),
+            TreeAction::ToggleFilter => {
+                if let Some(workspace) = &mut self.workspace {
+                    workspace.markdown_only ^= true;
+                }
+                Ok(())
+            }
+        };
+
+        if let Err(error) = result {
+            self.status = Some(error.to_string());
+        }
+        if moved_or_deleted {
+            // Whole folders may have moved, reindex rather than chase paths.
+            self.search_index = None;
+        }
+        self.refresh_workspace();
+    }
+
+    /// Follows a link from the rendered view. Notes replace the current
+    /// one unless it has unsaved changes or Ctrl is held.
+    fn follow(&mut self, ctx: &Context, link: Link) {
+        let new_tab = ctx.input(|i| i.modifiers.command);
+        match link {
+            Link::Wiki(target) => self.open_note(&target, new_tab),
+            Link::Fragment(fragment) => self.go_to_heading(ctx, &fragment),
+            Link::File(url) => self.open_link(ctx, &url, new_tab)
+        }
+    }
+
+    /// Opens a relative link to another Markdown file, scrolling to its
+    /// fragment if it has one.
+    fn open_link(&mut self, ctx: &Context, url: &str, new_tab: bool) {
+        let Some((path, fragment)) = workspace::note_link(url) else {
+            return;
+        };
+        let path = if path.is_absolute() {
+            path
+        } else {
+            let Some(dir) = self.editor().path().and_then(Path::parent) else {
+                self.status = Some(format!("Save this note before following {url}"));
+                return;
+            };
+            dir.join(path)
+        };
+        if !path.is_file() {
+            self.status = Some(format!("{} does not exist", path.display()));
+            return;
+        }
+
+        self.navigate(path, new_tab);
+        if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) {
+            self.go_to_heading(ctx, fragment);
+        }
+    }
+
+    /// Shows `path` in a new tab or in place of the current note, and
+    /// remembers the current note for `UIAction::Back`.
+    fn navigate(&mut self, path: PathBuf, new_tab: bool) {
+        let from = self.editor().path().map(Path::to_path_buf);
+        if self.show(path, new_tab) {
+            if let Some(from) = from {
+                self.back.push(from);
+                self.forward.clear();
+            }
+        }
+    }
+
+    /// Goes back to `path` or forward again, moving the current note onto
+    /// the opposite history.
+    fn step(&mut self, path: PathBuf, back: bool) {
+        let from = self.editor().path().map(Path::to_path_buf);
+        if !self.show(path, false) {
+            return;
+        }
+        if let Some(from) = from {
+            if back {
+                self.forward.push(from);
+            } else {
+                self.back.push(from);
+            }
+        }
+    }
+
+    /// Returns whether `path` is now the active note.
+    fn show(&mut self, path: PathBuf, new_tab: bool) -> bool {
+        if same_file(self.editor().path(), &path) {
+            return false;
+        }
+        let open = self.tabs.iter().any(|tab| same_file(tab.path(), &path));
+        if open || new_tab || self.editor().is_dirty() {
+            self.open_file(path.clone());
+            return same_file(self.editor().path(), &path);
+        }
+
+        match Editor::open(path.clone()) {
+            Ok(editor) => {
+                let mut old = std::mem::replace(self.editor(), editor);
+                if let Some(dir) = &self.recovery_dir {
+                    if let Err(error) = old.discard_recovery(dir) {
+                        self.status = Some(format!("Could not remove recovery file: {error}"));
+                    }
+                }
+                true
+            }
+            Err(error) => {
+                self.status = Some(format!("Could not open {}: {error}", path.display()));
+                false
+            }
+        }
+    }
+
+    /// Scrolls both the viewer and the editor to the heading with the slug
+    /// `fragment`.
+    fn go_to_heading(&mut self, ctx: &Context, fragment: &str) {
+        let editor = &mut self.tabs[self.active];
+        let headings = mk_core::parser::headings(&editor.code, editor.options);
+        let Some(index) = headings.iter().position(|heading| heading.slug.eq_ignore_ascii_case(fragment)) else {
+            self.status = Some(format!("No heading #{fragment} in this note"));
+            return;
+        };
+        if self.show_source {
+            editor.goto_line(headings[index].line);
+        }
+        viewer::scroll_to_heading(ctx, index);
+    }
+
+    /// Opens the note a wiki link names, creating it next to the current
+    /// note if there is none.
+    fn open_note(&mut self, target: &str, new_tab: bool) {
+        let found = self.workspace
+            .as_ref()
+            .and_then(|workspace| workspace.find_note(target))
+            .map(Path::to_path_buf);
+        if let Some(path) = found {
+            self.navigate(path, new_tab);
+            return;
+        }
+
+        let root = self.workspace.as_ref().map(|workspace| workspace.root().to_path_buf());
+        let dir = self.editor()
+            .canonical_path()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .or_else(|| root.clone());
+        let Some(dir) = dir else {
+            self.status = Some(format!("Save this note before creating \"{target}\""));
+            return;
+        };
+        // New notes stay inside the workspace, or next to this note without one.
+        let root = root.unwrap_or_else(|| dir.clone());
+        let Some(path) = workspace::new_note_path(&dir, target).filter(|path| path.starts_with(&root)) else {
+            self.status = Some(format!("Cannot create \"{target}\" outside {}", root.display()));
+            return;
+        };
+        if path.exists() {
+            self.navigate(path, new_tab);
+            return;
+        }
+        let parent = path.parent().map_or(Ok(()), fs::create_dir_all);
+        match parent.and_then(|()| workspace::create_file(&path)) {
+            Ok(()) => {
+                self.refresh_workspace();
+                self.navigate(path, new_tab);
+            }
+            Err(error) => self.status = Some(format!("Could not create {}: {error}", path.display()))
+        }
+    }
+
+    fn refresh_workspace(&mut self) {
+        if let Some(workspace) = &mut self.workspace {
+            if let Err(error) = workspace.refresh() {
+                self.status = Some(format!("Could not read {}: {error}", workspace.root().display()));
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        if self.editor().path().is_none() {
+            self.prompt = Some(Prompt::SaveAs(String::new(), None));
+            return;
+        }
+
+        match self.editor().save() {
+            Ok(()) => self.status = None,
+            Err(error) => self.status = Some(format!("Could not save: {error}"))
+        }
+    }
+
+    fn prompt_window(&mut self, ctx: &Context) {
+        let (title, button, path) = match &mut self.prompt {
+            Some(Prompt::OpenFile(path)) => ("Open file", "Open", path),
+            Some(Prompt::OpenFolder(path)) => ("Open folder", "Open", path),
+            Some(Prompt::SaveAs(path, _)) => ("Save as", "Save", path),
+            None => return
+        };
+
+        let mut open = true;
+        let mut confirmed = false;
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let response = ui.text_edit_singleline(path);
+                response.request_focus();
+                confirmed = response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                confirmed |= ui.button(button).clicked();
+            });
+
+        if !confirmed || path.is_empty() {
+            if !open {
+                self.prompt = None;
+            }
+            return;
+        }
+
+        let path = PathBuf::from(std::mem::take(path));
+        match self.prompt.take() {
+            Some(Prompt::OpenFile(_)) => self.open_file(path),
+            Some(Prompt::OpenFolder(_)) => self.open_folder(path),
+            Some(Prompt::SaveAs(_, None)) => match self.editor().save_as(path) {
+                Ok(()) => self.status = None,
+                Err(error) => self.status = Some(format!("Could not save: {error}"))
+            },
+            Some(Prompt::SaveAs(_, Some(id))) => {
+                let Some(index) = self.tab_index(&id) else {
+                    return;
+                };
+                match self.tabs[index].save_as(path) {
+                    Ok(()) => self.remove_tab(index),
+                    Err(error) => self.status = Some(format!("Could not save: {error}"))
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn close_window(&mut self, ctx: &Context) {
+        let Some(id) = &self.closing else {
+            return;
+        };
+        // The tab may have closed some other way while the dialog was open.
+        let Some(index) = self.tab_index(id) else {
+            self.closing = None;
+            return;
+        };
+
+        let mut choice = None;
+        egui::Window::new("Unsaved changes")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Save changes to {} before closing?", self.tabs[index].title()));
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.closing = None;
+                    }
+                });
+            });
+
+        match choice {
+            Some(true) => {
+                self.closing = None;
+                self.active = index;
+                if self.tabs[index].path().is_none() {
+                    let id = self.tabs[index].id().to_string();
+                    self.prompt = Some(Prompt::SaveAs(String::new(), Some(id)));
+                } else {
+                    match self.tabs[index].save() {
+                        Ok(()) => self.remove_tab(index),
+                        Err(error) => self.status = Some(format!("Could not save: {error}"))
+                    }
+                }
+            }
+            Some(false) => {
+                self.closing = None;
+                self.remove_tab(index);
+            }
+            None => {}
+        }
+    }
+
+    pub fn flush_autosave(&mut self) {
+        let Some(dir) = &self.recovery_dir else {
+            return;
+        };
+
+        self.last_autosave = Instant::now();
+        for tab in &mut self.tabs {
+            if let Err(error) = tab.autosave(dir) {
+                self.status = Some(format!("Autosave failed: {error}"));
+            }
+        }
+    }
+
+    fn autosave(&mut self, ctx: &Context) {
+        if self.last_autosave.elapsed() >= AUTOSAVE_INTERVAL {
+            self.flush_autosave();
+        }
+        ctx.request_repaint_after(AUTOSAVE_INTERVAL);
+    }
+
+    fn watch(&mut self, ctx: &Context) {
+        if self.watcher.is_none() {
+            let ctx = ctx.clone();
+            match Watcher::new(move || ctx.request_repaint()) {
+                Ok(watcher) => self.watcher = Some(watcher),
+                Err(error) => {
+                    self.status = Some(format!("Cannot watch files for changes: {error}"));
+                    return;
+                }
+            }
+        }
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+
+        let root = self.workspace.as_ref().map(|workspace| workspace.root().to_path_buf());
+        if root != self.watched_tree {
+            if let Some(old) = self.watched_tree.take() {
+                let _ = watcher.unwatch_tree(&old);
+            }
+            if let Some(root) = &root {
+                if let Err(error) = watcher.watch_tree(root) {
+                    self.status = Some(format!("Cannot watch {}: {error}", root.display()));
+                }
+            }
+            self.watched_tree = root;
+        }
+
+        let paths = self.tabs
+            .iter()
+            .filter_map(|tab| tab.canonical_path().map(Path::to_path_buf))
+            .collect::<HashSet<_>>();
+        for old in self.watched.difference(&paths) {
+            let _ = watcher.unwatch(old);
+        }
+        for new in paths.difference(&self.watched) {
+            if let Err(error) = watcher.watch(new) {
+                self.status = Some(format!("Cannot watch {}: {error}", new.display()));
+            }
+        }
+        self.watched = paths;
+
+        let changed = watcher.changed();
+        if changed.is_empty() {
+            return;
+        }
+
+        let in_workspace = self.workspace
+            .as_ref()
+            .is_some_and(|workspace| changed.iter().any(|path| workspace.contains(path)));
+        if in_workspace {
+            self.refresh_workspace();
+        }
+        if let Some(index) = &self.search_index {
+            for path in changed.iter().filter(|path| workspace::is_markdown(path)) {
+                index.update(path.clone());
+            }
+        }
+
+        for tab in &mut self.tabs {
+            let Some(path) = tab.canonical_path().map(Path::to_path_buf) else {
+                continue;
+            };
+            if !changed.contains(&path) {
+                continue;
+            }
+            match tab.reload() {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    self.status = Some(format!("{} was removed from disk", tab.title()));
+                }
+                Err(error) => self.status = Some(format!("Could not reload: {error}"))
+            }
+        }
+    }
+
+    fn conflict_banner(&mut self, ctx: &Context) {
+        let editor = &mut self.tabs[self.active];
+        let Some(disk) = editor.conflict() else {
+            self.show_conflict_diff = false;
+            return;
+        };
+
+        let mut keep_mine = false;
+        let mut take_disk = false;
+        egui::TopBottomPanel::top("conflict")
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!("{} changed on disk while you were editing it.", editor.title())
+                    );
+                    keep_mine = ui.button("Keep mine").clicked();
+                    take_disk = ui.button("Take disk version").clicked();
+                    ui.toggle_value(&mut self.show_conflict_diff, "View diff");
+                });
+            });
+
+        egui::Window::new("Disk version vs. yours")
+            .open(&mut self.show_conflict_diff)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                ScrollArea::both().show(ui, |ui| {
+                    diff::diff(ui, disk, &editor.code);
+                });
+            });
+
+        if keep_mine {
+            editor.keep_mine();
+        } else if take_disk {
+            editor.take_disk();
+        }
+    }
+
     fn ui(&mut self, ui: &mut Ui) {
+        let editor = &mut self.tabs[self.active];
+        let mut clicked = None;
         if self.show_source && self.show_rendered {
-            ui.columns(2, |columns| { 
+            ui.columns(2, |columns| {
+                editor.toolbar(&mut columns[0]);
                 ScrollArea::vertical()
                     .id_salt("source")
-                    .show(&mut columns[0], |ui| self.editor.ui(ui));
+                    .show(&mut columns[0], |ui| editor.ui(ui));
                 ScrollArea::vertical()
                     .id_salt("rendered")
                     .show(&mut columns[1], |ui| {
-                        crate::widgets::viewer::mark(ui, &self.editor.code);
+                        clicked = viewer::mark(ui, &editor.code, editor.options);
                     })
             });
-        } else if self.show_source { 
+        } else if self.show_source {
+            editor.toolbar(ui);
             ScrollArea::vertical()
                 .id_salt("source")
-                .show(ui, |ui| self.editor.ui(ui));
+                .show(ui, |ui| editor.ui(ui));
         } else if self.show_rendered {
             ScrollArea::vertical()
                 .id_salt("rendered")
                 .show(ui, |ui| {
-                    crate::widgets::viewer::mark(ui, &self.editor.code);
+                    clicked = viewer::mark(ui, &editor.code, editor.options);
                 });
         }
+
+        if let Some(link) = clicked {
+            self.follow(ui.ctx(), link);
+        }
+    }
+}
+
+fn same_file(a: Option<&Path>, b: &Path) -> bool {
+    let Some(a) = a else {
+        return false;
+    };
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b
     }
 }
@@ -1,8 +1,13 @@
 pub use mk_core::eframe::egui::{self};
+use std::path::PathBuf;
+use egui::{Key, KeyboardShortcut, Modifiers};
+use mk_core::recovery::Recovered;
 
 mod main;
+mod recovery;
 
 pub use main::MainUI;
+pub use recovery::RecoveryUI;
 
 pub trait UIStateVariant {
     fn update_panel(&mut self, ctx: &egui::Context) -> UIAction;
@@ -10,27 +15,144 @@ pub trait UIStateVariant {
 
 #[derive(Clone, Debug)]
 pub enum UIAction {
-    Nothing
+    Nothing,
+    Open(Option<PathBuf>),
+    Restore(Vec<Recovered>, Option<PathBuf>),
+    NewTab,
+    OpenFile,
+    OpenFolder,
+    Save,
+    SaveAs,
+    CloseTab,
+    QuickOpen,
+    Search,
+    CommandPalette,
+    ToggleSidebar,
+    ToggleSource,
+    ToggleRendered,
+    ToggleParagraphs,
+    ToggleDialect,
+    ToggleMath,
+    Back,
+    Forward
+}
+
+impl UIAction {
+    /// Everything the user can run from menus, keybindings and the command
+    /// palette. Shortcuts with Shift come before the same shortcut without
+    /// it, since egui lets Ctrl+P match while Shift is also held.
+    pub const COMMANDS: [UIAction; 17] = [
+        UIAction::NewTab,
+        UIAction::OpenFile,
+        UIAction::OpenFolder,
+        UIAction::Save,
+        UIAction::SaveAs,
+        UIAction::CloseTab,
+        UIAction::CommandPalette,
+        UIAction::QuickOpen,
+        UIAction::Search,
+        UIAction::ToggleSidebar,
+        UIAction::ToggleSource,
+        UIAction::ToggleRendered,
+        UIAction::ToggleParagraphs,
+        UIAction::ToggleDialect,
+        UIAction::ToggleMath,
+        UIAction::Back,
+        UIAction::Forward
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UIAction::Nothing => "Nothing",
+            UIAction::Open(_) => "Open",
+            UIAction::Restore(..) => "Restore",
+            UIAction::NewTab => "New",
+            UIAction::OpenFile => "Open file…",
+            UIAction::OpenFolder => "Open folder…",
+            UIAction::Save => "Save",
+            UIAction::SaveAs => "Save as…",
+            UIAction::CloseTab => "Close tab",
+            UIAction::QuickOpen => "Quick open…",
+            UIAction::Search => "Search notes…",
+            UIAction::CommandPalette => "Command palette…",
+            UIAction::ToggleSidebar => "Toggle sidebar",
+            UIAction::ToggleSource => "Toggle source",
+            UIAction::ToggleRendered => "Toggle rendered view",
+            UIAction::ToggleParagraphs => "Toggle paragraph mode",
+            UIAction::ToggleDialect => "Toggle CommonMark dialect",
+            UIAction::ToggleMath => "Toggle math mode",
+            UIAction::Back => "Back",
+            UIAction::Forward => "Forward"
+        }
+    }
+
+    pub fn shortcut(&self) -> Option<KeyboardShortcut> {
+        let command = Modifiers::COMMAND;
+        let command_shift = Modifiers::COMMAND.plus(Modifiers::SHIFT);
+        let (modifiers, key) = match self {
+            UIAction::NewTab => (command, Key::N),
+            UIAction::OpenFile => (command, Key::O),
+            UIAction::Save => (command, Key::S),
+            UIAction::CloseTab => (command, Key::W),
+            UIAction::CommandPalette => (command_shift, Key::P),
+            UIAction::QuickOpen => (command, Key::P),
+            UIAction::Search => (command_shift, Key::F),
+            UIAction::ToggleSidebar => (command, Key::B),
+            UIAction::Back => (command, Key::OpenBracket),
+            UIAction::Forward => (command, Key::CloseBracket),
+            _ => return None
+        };
+        Some(KeyboardShortcut::new(modifiers, key))
+    }
 }
 
 pub enum UIState {
-    Main(MainUI)
+    Main(Box<MainUI>),
+    Recovery(RecoveryUI)
 }
 
 impl Default for UIState {
     fn default() -> Self {
-        UIState::Main(MainUI::default())
+        UIState::new(None)
     }
 }
 
 impl UIState {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let recovered = mk_core::recovery::recovery_dir()
+            .and_then(|dir| mk_core::recovery::list(&dir).ok())
+            .unwrap_or_default();
+
+        if recovered.is_empty() {
+            UIState::Main(Box::new(MainUI::open(path)))
+        } else {
+            UIState::Recovery(RecoveryUI::new(recovered, path))
+        }
+    }
+
+    pub fn on_exit(&mut self) {
+        if let UIState::Main(panel) = self {
+            panel.flush_autosave();
+        }
+    }
+
     pub fn update(&mut self, ctx: &egui::Context) {
         let response = match self {
-            UIState::Main(panel) => panel.update_panel(ctx)
+            UIState::Main(panel) => panel.update_panel(ctx),
+            UIState::Recovery(panel) => panel.update_panel(ctx)
         };
 
         match response {
             UIAction::Nothing => {}
+            UIAction::Open(path) => *self = UIState::Main(Box::new(MainUI::open(path))),
+            UIAction::Restore(recovered, launch) => {
+                *self = UIState::Main(Box::new(MainUI::restore(recovered, launch)));
+            }
+            command => {
+                if let UIState::Main(panel) = self {
+                    panel.perform(command);
+                }
+            }
         }
     }
 }
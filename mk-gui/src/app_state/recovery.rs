@@ -0,0 +1,155 @@
+use std::{fs, path::PathBuf, time::SystemTime};
+use mk_core::eframe::egui::{
+    self, Context, Ui, ScrollArea
+};
+use mk_core::recovery::{self, Recovered};
+use super::super::widgets::diff;
+use super::{UIAction, UIStateVariant};
+
+struct Entry {
+    recovered: Recovered,
+    disk: String,
+    /// Whether to bring this buffer back when restoring.
+    selected: bool
+}
+
+pub struct RecoveryUI {
+    entries: Vec<Entry>,
+    launch: Option<PathBuf>,
+    status: Option<String>,
+    action: Option<UIAction>
+}
+
+impl RecoveryUI {
+    pub fn new(recovered: Vec<Recovered>, launch: Option<PathBuf>) -> Self {
+        let entries = recovered
+            .into_iter()
+            .map(|recovered| Entry {
+                disk: recovered.original
+                    .as_ref()
+                    .and_then(|path| fs::read_to_string(path).ok())
+                    .unwrap_or_default(),
+                recovered,
+                selected: true
+            })
+            .collect();
+
+        RecoveryUI {
+            entries,
+            launch,
+            status: None,
+            action: None
+        }
+    }
+}
+
+impl UIStateVariant for RecoveryUI {
+    fn update_panel(&mut self, ctx: &Context) -> UIAction {
+        if let Some(status) = &self.status {
+            let mut dismissed = false;
+            egui::TopBottomPanel::bottom("status")
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(status);
+                        dismissed = ui.small_button("✖").clicked();
+                    });
+                });
+            if dismissed {
+                self.status = None;
+            }
+        }
+
+        egui::CentralPanel::default()
+            .show(ctx, |ui| {
+                self.ui(ui);
+            });
+
+        if self.entries.is_empty() && self.action.is_none() {
+            self.action = Some(UIAction::Open(self.launch.take()));
+        }
+
+        self.action.take().unwrap_or(UIAction::Nothing)
+    }
+}
+
+impl RecoveryUI {
+    fn ui(&mut self, ui: &mut Ui) {
+        ui.heading("Recover unsaved changes");
+        if self.entries.iter().any(|entry| !entry.recovered.clean) {
+            ui.label("Marko did not close cleanly. These buffers were autosaved before it stopped.");
+        } else {
+            ui.label("These buffers had unsaved changes when Marko was last closed.");
+        }
+        ui.add_space(8.0);
+
+        let mut discarded = None;
+        ScrollArea::vertical().show(ui, |ui| {
+            for (index, entry) in self.entries.iter_mut().enumerate() {
+                ui.group(|ui| {
+                    ui.set_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut entry.selected, "");
+                        ui.strong(title(&entry.recovered));
+                        if let Some(modified) = entry.recovered.modified {
+                            ui.weak(format!("autosaved {}", ago(modified)));
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("Discard").clicked() {
+                                discarded = Some(index);
+                            }
+                        });
+                    });
+
+                    egui::CollapsingHeader::new("Changes against the file on disk")
+                        .id_salt(&entry.recovered.id)
+                        .show(ui, |ui| {
+                            diff::diff(ui, &entry.disk, &entry.recovered.code);
+                        });
+                });
+            }
+        });
+
+        if let Some(index) = discarded {
+            let entry = self.entries.remove(index);
+            if let Some(dir) = recovery::recovery_dir() {
+                if let Err(error) = recovery::remove(&dir, &entry.recovered.id) {
+                    self.status = Some(format!("Could not discard recovered buffer: {error}"));
+                }
+            }
+        }
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            // Buffers left unselected stay in the recovery directory and are
+            // offered again next time.
+            let selected = self.entries.iter().any(|entry| entry.selected);
+            if ui.add_enabled(selected, egui::Button::new("Restore selected")).clicked() {
+                let recovered = self.entries
+                    .iter()
+                    .filter(|entry| entry.selected)
+                    .map(|entry| entry.recovered.clone())
+                    .collect();
+                self.action = Some(UIAction::Restore(recovered, self.launch.take()));
+            }
+            if ui.button("Continue without restoring").clicked() {
+                self.action = Some(UIAction::Open(self.launch.take()));
+            }
+        });
+    }
+}
+
+fn title(recovered: &Recovered) -> String {
+    recovered.original
+        .as_ref()
+        .map_or_else(|| "Untitled".to_string(), |path| path.display().to_string())
+}
+
+fn ago(time: SystemTime) -> String {
+    let seconds = time.elapsed().map_or(0, |elapsed| elapsed.as_secs());
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", seconds / 60),
+        3600..=86399 => format!("{} h ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400)
+    }
+}
@@ -0,0 +1,39 @@
+use std::fs;
+use mk_core::eframe::egui::{Context, FontData, FontDefinitions, FontFamily};
+
+/// Emoji and symbol fonts that ship with common systems, most of them
+/// monochrome since egui cannot draw colour glyphs. egui's own emoji font
+/// is an old subset that lacks newer emoji, so these are tried after it.
+///
+/// Bundling a current emoji font, as the emoji request asked, is descoped
+/// until one can be vendored with its licence: the only emoji font the
+/// build has access to is the subset egui already embeds. Without a system
+/// font from this list the viewer shows the shortcode of emoji it cannot
+/// draw.
+const EMOJI_FONTS: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/noto/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/google-noto-emoji/NotoEmoji-Regular.ttf",
+    "/usr/share/fonts/truetype/ancient-scripts/Symbola_hint.ttf",
+    "/usr/share/fonts/TTF/Symbola.ttf",
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "C:\\Windows\\Fonts\\seguisym.ttf",
+    "/System/Library/Fonts/Apple Symbols.ttf"
+];
+
+/// Adds the first emoji font found on this system as a fallback for both
+/// font families.
+pub fn install(ctx: &Context) {
+    let Some((path, data)) = EMOJI_FONTS
+        .iter()
+        .find_map(|path| Some((*path, fs::read(path).ok()?))) else {
+        return;
+    };
+
+    let mut fonts = FontDefinitions::default();
+    fonts.font_data.insert(path.to_string(), FontData::from_owned(data));
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts.families.entry(family).or_default().push(path.to_string());
+    }
+    ctx.set_fonts(fonts);
+}
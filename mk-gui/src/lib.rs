@@ -1,6 +1,8 @@
 mod app;
 mod app_state;
+mod fonts;
 mod widgets;
 
 pub use app::MarkoApp;
+pub use widgets::viewer::Extensions;
 pub use mk_core::eframe;
@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+use mk_core::eframe::egui::{ScrollArea, Ui};
+use mk_core::search::Index;
+
+#[derive(Default)]
+pub struct Backlinks {
+    /// The note and index generation `links` were found for.
+    searched: Option<(PathBuf, u64)>,
+    links: Vec<PathBuf>
+}
+
+impl Backlinks {
+    /// Lists the notes linking to `open`, returning the one clicked.
+    pub fn ui(&mut self, ui: &mut Ui, index: &Index, root: &Path, open: Option<&Path>) -> Option<PathBuf> {
+        let Some(open) = open else {
+            ui.weak("Save this note to see what links to it");
+            return None;
+        };
+
+        let searched = (open.to_path_buf(), index.generation());
+        if self.searched.as_ref() != Some(&searched) {
+            self.links = index.backlinks(open);
+            self.searched = Some(searched);
+        }
+
+        if index.is_indexing() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.weak("Indexing…");
+            });
+        }
+        if self.links.is_empty() {
+            ui.weak("No notes link here");
+            return None;
+        }
+
+        let mut clicked = None;
+        ScrollArea::vertical()
+            .id_salt("backlinks")
+            .show(ui, |ui| {
+                for path in &self.links {
+                    let name = path.strip_prefix(root).unwrap_or(path).display().to_string();
+                    if ui.selectable_label(false, name).clicked() {
+                        clicked = Some(path.clone());
+                    }
+                }
+            });
+        clicked
+    }
+}
@@ -0,0 +1,128 @@
+use mk_core::eframe::egui::{
+    self, text::LayoutJob, Align, Align2,
+    Context, Key, KeyboardShortcut, Layout,
+    Modifiers, ScrollArea, TextFormat, TextStyle
+};
+use mk_core::fuzzy::fuzzy_match;
+
+pub struct Command {
+    pub name: &'static str,
+    pub shortcut: Option<KeyboardShortcut>,
+    pub enabled: bool
+}
+
+#[derive(Default)]
+pub struct CommandPalette {
+    query: String,
+    selected: usize
+}
+
+impl CommandPalette {
+    /// Returns the index into `commands` of the one the user picked, and
+    /// clears `open` once done.
+    pub fn show(&mut self, ctx: &Context, commands: &[Command], open: &mut bool) -> Option<usize> {
+        let mut results = commands
+            .iter()
+            .enumerate()
+            .filter(|(_, command)| command.enabled)
+            .filter_map(|(index, command)| {
+                let found = fuzzy_match(&self.query, command.name)?;
+                Some((found.score, index, found.positions))
+            })
+            .collect::<Vec<_>>();
+        // Keep the declared order while nothing is typed.
+        if !self.query.trim().is_empty() {
+            results.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+        }
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| (
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+            i.consume_key(Modifiers::NONE, Key::Enter),
+            i.consume_key(Modifiers::NONE, Key::Escape)
+        ));
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+
+        let mut picked = enter.then_some(self.selected).filter(|_| !results.is_empty());
+        let mut query_changed = false;
+
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+            .fixed_size([420.0, 0.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Run a command")
+                        .desired_width(f32::INFINITY)
+                );
+                response.request_focus();
+                query_changed = response.changed();
+
+                ui.separator();
+                ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for (row, (_, index, positions)) in results.iter().enumerate() {
+                            let command = &commands[*index];
+                            let selected = row == self.selected;
+                            let response = ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                if let Some(shortcut) = &command.shortcut {
+                                    ui.weak(ui.ctx().format_shortcut(shortcut));
+                                }
+                                ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
+                                    let job = name_job(ui, command.name, positions);
+                                    ui.add_sized(
+                                        [ui.available_width(), ui.spacing().interact_size.y],
+                                        egui::SelectableLabel::new(selected, job)
+                                    )
+                                }).inner
+                            }).inner;
+                            if selected && (up || down) {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                picked = Some(row);
+                            }
+                        }
+                        if results.is_empty() {
+                            ui.weak("No matching commands");
+                        }
+                    });
+            });
+
+        if query_changed {
+            self.selected = 0;
+        }
+        if escape || picked.is_some() {
+            *open = false;
+        }
+
+        results.get(picked?).map(|(_, index, _)| *index)
+    }
+}
+
+fn name_job(ui: &egui::Ui, name: &str, positions: &[usize]) -> LayoutJob {
+    let font_id = TextStyle::Body.resolve(ui.style());
+    let normal = TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+    let matched = TextFormat::simple(font_id, ui.visuals().strong_text_color());
+
+    let mut job = LayoutJob::default();
+    for (index, c) in name.chars().enumerate() {
+        let format = if positions.contains(&index) {
+            matched.clone()
+        } else {
+            normal.clone()
+        };
+        job.append(c.encode_utf8(&mut [0; 4]), 0.0, format);
+    }
+    job
+}
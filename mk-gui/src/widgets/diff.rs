@@ -0,0 +1,37 @@
+use mk_core::diff;
+use mk_core::eframe::egui::{
+    text::LayoutJob, Color32, TextFormat,
+    TextStyle, Ui
+};
+
+pub fn diff(ui: &mut Ui, old: &str, new: &str) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let text_color = ui.visuals().text_color();
+    let (added, removed) = if ui.visuals().dark_mode {
+        (Color32::from_rgb(30, 70, 30), Color32::from_rgb(90, 30, 30))
+    } else {
+        (Color32::from_rgb(210, 245, 210), Color32::from_rgb(250, 215, 215))
+    };
+
+    let mut job = LayoutJob::default();
+    for line in diff::lines(old, new) {
+        let (prefix, text, background) = match line {
+            diff::Line::Same(text) => ("  ", text, Color32::TRANSPARENT),
+            diff::Line::Added(text) => ("+ ", text, added),
+            diff::Line::Removed(text) => ("- ", text, removed)
+        };
+        let format = TextFormat {
+            font_id: font_id.clone(),
+            color: text_color,
+            background,
+            ..Default::default()
+        };
+        job.append(prefix, 0.0, format.clone());
+        job.append(text, 0.0, format.clone());
+        if !text.ends_with('\n') {
+            job.append("\n", 0.0, format);
+        }
+    }
+
+    ui.label(job);
+}
@@ -1,46 +1,400 @@
+use std::{
+    fs, io,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf}
+};
 use mk_core::eframe::egui::{
-    self, Context, Ui, TextEdit, 
-    TextStyle, TextFormat, FontId,
-    Color32, text::LayoutJob,
-    ScrollArea, Align
+    self, Ui, TextEdit, Key, Modifiers,
+    TextStyle, TextFormat, FontId, Frame,
+    Color32, text::LayoutJob, Align, Order,
+    text::{CCursor, CCursorRange}
 };
-use mk_core::Highlighter;
+use mk_core::{emoji, parser::ParserOptions, recovery, Highlighter};
+use super::emoji_picker::EmojiPicker;
+
+/// Most shortcodes the completion popup lists.
+const MAX_COMPLETIONS: usize = 8;
 
 pub struct Editor {
     pub code: String,
-    highlighter: Highlighter
+    path: Option<PathBuf>,
+    /// `path` with symlinks resolved, kept so it is not looked up every
+    /// frame. Unset until the file exists.
+    canonical: Option<PathBuf>,
+    saved: String,
+    recovery_id: String,
+    /// Whether a recovery file may exist for this buffer, written by an
+    /// autosave or left behind by an earlier session.
+    recovery_stored: bool,
+    /// Hash of the code last autosaved.
+    autosaved: Option<u64>,
+    conflict: Option<String>,
+    goto: Option<usize>,
+    /// Char index to put the cursor at the next time the editor is shown.
+    select: Option<usize>,
+    /// Char index of the cursor while the editor has focus.
+    cursor: Option<usize>,
+    completion: Option<Completion>,
+    /// Colon of a completion closed with Escape, so it stays closed.
+    dismissed: Option<usize>,
+    emoji_picker: EmojiPicker,
+    highlighter: Highlighter,
+    pub options: ParserOptions
+}
+
+/// Emoji shortcodes offered while the text before the cursor looks like
+/// `:tad`.
+struct Completion {
+    /// Char indices of the colon and the cursor.
+    start: usize,
+    end: usize,
+    query: String,
+    matches: Vec<(&'static str, &'static str)>,
+    selected: usize
 }
 
 impl Default for Editor {
     fn default() -> Self {
+        let code = "# Marko".to_string();
         Editor {
-            code: "# Marko".to_string(),
-            highlighter: Default::default()
+            saved: code.clone(),
+            code,
+            path: None,
+            canonical: None,
+            recovery_id: recovery::new_id(),
+            recovery_stored: false,
+            autosaved: None,
+            conflict: None,
+            goto: None,
+            select: None,
+            cursor: None,
+            completion: None,
+            dismissed: None,
+            emoji_picker: EmojiPicker::default(),
+            highlighter: Default::default(),
+            options: Default::default()
         }
     }
 }
 
 impl Editor {
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let code = fs::read_to_string(&path)?;
+        Ok(Editor {
+            saved: code.clone(),
+            code,
+            canonical: path.canonicalize().ok(),
+            path: Some(path),
+            ..Default::default()
+        })
+    }
+
+    /// Reopens a buffer left behind by an earlier session under a recovery
+    /// id of this instance. The caller removes the old recovery file once
+    /// the buffer is autosaved under the new one.
+    pub fn recovered(recovered: recovery::Recovered) -> Self {
+        let saved = recovered.original
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        Editor {
+            code: recovered.code,
+            canonical: recovered.original.as_ref().and_then(|path| path.canonicalize().ok()),
+            path: recovered.original,
+            saved,
+            ..Default::default()
+        }
+    }
+
+    /// Tells buffers apart for as long as they are open.
+    pub fn id(&self) -> &str {
+        &self.recovery_id
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn canonical_path(&self) -> Option<&Path> {
+        self.canonical.as_deref()
+    }
+
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.canonical = path.canonicalize().ok();
+        self.path = Some(path);
+    }
+
+    pub fn title(&self) -> String {
+        self.path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map_or_else(|| "Untitled".to_string(), |name| name.to_string_lossy().into_owned())
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.code != self.saved
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "document has no path"));
+        };
+        fs::write(path, &self.code)?;
+        if self.canonical.is_none() {
+            self.canonical = path.canonicalize().ok();
+        }
+        self.saved.clone_from(&self.code);
+        self.conflict = None;
+        Ok(())
+    }
+
+    pub fn save_as(&mut self, path: PathBuf) -> io::Result<()> {
+        fs::write(&path, &self.code)?;
+        self.canonical = path.canonicalize().ok();
+        self.path = Some(path);
+        self.saved.clone_from(&self.code);
+        self.conflict = None;
+        Ok(())
+    }
+
+    /// Picks up a change another program made to the file. Clean buffers
+    /// are reloaded, dirty ones keep their text and record a conflict.
+    pub fn reload(&mut self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let disk = fs::read_to_string(path)?;
+        if disk == self.saved {
+            // Our own save, or a touch that did not change anything.
+            self.conflict = None;
+        } else if self.is_dirty() {
+            self.conflict = Some(disk);
+        } else {
+            self.code.clone_from(&disk);
+            self.saved = disk;
+        }
+        Ok(())
+    }
+
+    /// The version on disk while it conflicts with unsaved changes.
+    pub fn conflict(&self) -> Option<&str> {
+        self.conflict.as_deref()
+    }
+
+    pub fn keep_mine(&mut self) {
+        if let Some(disk) = self.conflict.take() {
+            self.saved = disk;
+        }
+    }
+
+    pub fn take_disk(&mut self) {
+        if let Some(disk) = self.conflict.take() {
+            self.code.clone_from(&disk);
+            self.saved = disk;
+        }
+    }
+
+    /// Writes the buffer to the recovery directory if it changed since the
+    /// last autosave, or drops the recovery file once nothing is unsaved.
+    pub fn autosave(&mut self, dir: &Path) -> io::Result<()> {
+        if !self.is_dirty() {
+            self.autosaved = None;
+            if self.recovery_stored {
+                recovery::remove(dir, &self.recovery_id)?;
+                self.recovery_stored = false;
+            }
+            return Ok(());
+        }
+
+        let mut hasher = DefaultHasher::new();
+        self.code.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.autosaved != Some(hash) {
+            recovery::store(dir, &self.recovery_id, self.path(), &self.code)?;
+            self.recovery_stored = true;
+            self.autosaved = Some(hash);
+        }
+        Ok(())
+    }
+
+    /// Moves the cursor to the start of a zero based `line` and scrolls
+    /// it into view the next time the editor is shown.
+    pub fn goto_line(&mut self, line: usize) {
+        self.goto = Some(line);
+    }
+
+    pub fn discard_recovery(&mut self, dir: &Path) -> io::Result<()> {
+        self.autosaved = None;
+        recovery::remove(dir, &self.recovery_id)?;
+        self.recovery_stored = false;
+        Ok(())
+    }
+
+    /// Inserts `text` at the cursor, or at the end if the editor has not
+    /// had focus yet.
+    pub fn insert(&mut self, text: &str) {
+        let at = self.cursor.unwrap_or_else(|| self.code.chars().count());
+        self.code.insert_str(byte_index(&self.code, at), text);
+        self.select = Some(at + text.chars().count());
+    }
+
+    pub fn toolbar(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let mut picked = None;
+            ui.menu_button("😀", |ui| {
+                picked = self.emoji_picker.ui(ui);
+                if picked.is_some() {
+                    ui.close_menu();
+                }
+            }).response.on_hover_text("Insert emoji");
+            if let Some(shortcode) = picked {
+                self.insert(&format!(":{shortcode}:"));
+            }
+        });
+    }
+
     pub fn ui(&mut self, ui: &mut Ui) {
+        let options = self.options;
         let mut layouter = |ui: &Ui, mark: &str, wrap_width: f32| {
-            let mut layout_job = self.highlighter.highlight(ui.style(), mark);
+            let mut layout_job = self.highlighter.highlight(ui.style(), mark, options);
             layout_job.wrap.max_width = wrap_width;
             ui.fonts(|f| f.layout_job(layout_job))
         };
 
-        ui.horizontal_top(|ui| {
+        // Keys the completion popup uses must not reach the text edit.
+        let (up, down, accept, escape) = match self.completion {
+            Some(_) => ui.input_mut(|i| (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Enter) || i.consume_key(Modifiers::NONE, Key::Tab),
+                i.consume_key(Modifiers::NONE, Key::Escape)
+            )),
+            None => (false, false, false, false)
+        };
+
+        let output = ui.horizontal_top(|ui| {
             Self::numlines(ui, self.code.as_str());
-            ui.add(
-                egui::TextEdit::multiline(&mut self.code)
-                    .frame(false)
-                    .code_editor()
-                    .desired_width(f32::INFINITY)
-                    .desired_rows(10)
-                    .layouter(&mut layouter)
-            );
+            let mut output = egui::TextEdit::multiline(&mut self.code)
+                .id_salt(&self.recovery_id)
+                .frame(false)
+                .code_editor()
+                .desired_width(f32::INFINITY)
+                .desired_rows(10)
+                .layouter(&mut layouter)
+                .show(ui);
+
+            let line_start = self.goto.take().map(|line| {
+                self.code
+                    .split_inclusive('\n')
+                    .take(line)
+                    .map(|line| line.chars().count())
+                    .sum()
+            });
+            let align = line_start.map(|_| Align::Center);
+            if let Some(index) = line_start.or(self.select.take()) {
+                let cursor = CCursor::new(index);
+                output.state.cursor.set_char_range(Some(CCursorRange::one(cursor)));
+                output.state.clone().store(ui.ctx(), output.response.id);
+                output.response.request_focus();
+
+                let rect = output.galley
+                    .pos_from_ccursor(cursor)
+                    .translate(output.galley_pos.to_vec2());
+                ui.scroll_to_rect(rect, align);
+            }
+            output
+        }).inner;
+
+        self.cursor = output.cursor_range.map(|range| range.primary.ccursor.index);
+        let mut accepted = None;
+        if let Some(completion) = &mut self.completion {
+            let last = completion.matches.len() - 1;
+            if up {
+                completion.selected = completion.selected.checked_sub(1).unwrap_or(last);
+            }
+            if down {
+                completion.selected = if completion.selected == last { 0 } else { completion.selected + 1 };
+            }
+            if accept {
+                accepted = Some(completion.matches[completion.selected].0);
+            }
+            if escape {
+                self.dismissed = Some(completion.start);
+            }
+        }
+        if accepted.is_none() {
+            self.update_completion();
+        }
+
+        if let Some(completion) = &self.completion {
+            let rect = output.galley
+                .pos_from_ccursor(CCursor::new(completion.end))
+                .translate(output.galley_pos.to_vec2());
+            egui::Area::new(output.response.id.with("emoji completion"))
+                .order(Order::Foreground)
+                .fixed_pos(rect.left_bottom())
+                .show(ui.ctx(), |ui| {
+                    Frame::popup(ui.style()).show(ui, |ui| {
+                        for (index, (shortcode, emoji)) in completion.matches.iter().enumerate() {
+                            let label = format!("{emoji}  :{shortcode}:");
+                            if ui.selectable_label(index == completion.selected, label).clicked() {
+                                accepted = Some(shortcode);
+                            }
+                        }
+                    });
+                });
+        }
+
+        if let Some(shortcode) = accepted {
+            self.complete(shortcode);
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Opens, refreshes or closes the completion for the text before the
+    /// cursor.
+    fn update_completion(&mut self) {
+        let typed = self.cursor.and_then(|cursor| {
+            let end = byte_index(&self.code, cursor);
+            let colon = self.code[..end].rfind(':')?;
+            let query = &self.code[colon + 1..end];
+            let valid = query.len() >= 2
+                && query.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'))
+                && !self.code[..colon].chars().next_back().is_some_and(char::is_alphanumeric);
+            valid.then(|| (self.code[..colon].chars().count(), cursor, query))
+        });
+        let Some((start, end, query)) = typed.filter(|(start, ..)| self.dismissed != Some(*start)) else {
+            self.completion = None;
+            return;
+        };
+
+        if self.completion.as_ref().is_some_and(|completion| completion.query == query) {
+            return;
+        }
+        let matches = emoji::search(query)
+            .into_iter()
+            .take(MAX_COMPLETIONS)
+            .collect::<Vec<_>>();
+        self.completion = (!matches.is_empty()).then(|| Completion {
+            start,
+            end,
+            query: query.to_string(),
+            matches,
+            selected: 0
         });
     }
 
+    /// Replaces the `:query` being completed with the full shortcode.
+    fn complete(&mut self, shortcode: &str) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let text = format!(":{shortcode}:");
+        let range = byte_index(&self.code, completion.start)..byte_index(&self.code, completion.end);
+        self.code.replace_range(range, &text);
+        self.select = Some(completion.start + text.chars().count());
+    }
+
     fn numlines(ui: &mut Ui, code: &str) {
         let total = if code.ends_with('\n') || code.is_empty() {
             code.lines().count() + 1
@@ -77,7 +431,7 @@ impl Editor {
         
         ui.add(
             TextEdit::multiline(&mut counter)
-                .id_source(format!("numlines"))
+                .id_source("numlines")
                 .font(TextStyle::Monospace)
                 .interactive(false)
                 .frame(false)
@@ -87,3 +441,7 @@ impl Editor {
         );
     }
 }
+
+fn byte_index(s: &str, char_index: usize) -> usize {
+    s.char_indices().nth(char_index).map_or(s.len(), |(index, _)| index)
+}
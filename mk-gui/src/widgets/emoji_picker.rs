@@ -0,0 +1,54 @@
+use mk_core::eframe::egui::{self, RichText, ScrollArea, Ui};
+use mk_core::emoji;
+
+/// Stop listing after this many, the full set is a couple of thousand.
+const MAX_SHOWN: usize = 320;
+
+#[derive(Default)]
+pub struct EmojiPicker {
+    query: String,
+    found: Option<(String, Vec<(&'static str, &'static str)>)>
+}
+
+impl EmojiPicker {
+    /// Returns the shortcode of the emoji the user picked.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<&'static str> {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.query)
+                .hint_text("Search emoji")
+                .desired_width(240.0)
+        );
+        response.request_focus();
+
+        let query = self.query.trim().to_lowercase();
+        let found = match &self.found {
+            Some((searched, found)) if *searched == query => found,
+            _ => &self.found.insert((query.clone(), emoji::search(&query))).1
+        };
+
+        let mut picked = None;
+        ScrollArea::vertical()
+            .max_height(240.0)
+            .show(ui, |ui| {
+                ui.set_max_width(240.0);
+                ui.horizontal_wrapped(|ui| {
+                    for &(shortcode, emoji) in found.iter().take(MAX_SHOWN) {
+                        let button = egui::Button::new(RichText::new(emoji).size(18.0)).frame(false);
+                        if ui.add(button).on_hover_text(format!(":{shortcode}:")).clicked() {
+                            picked = Some(shortcode);
+                        }
+                    }
+                });
+                if found.is_empty() {
+                    ui.weak("No matching emoji");
+                } else if found.len() > MAX_SHOWN {
+                    ui.weak("Type to find more");
+                }
+            });
+
+        if picked.is_some() {
+            self.query.clear();
+        }
+        picked
+    }
+}
@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+use mk_core::eframe::egui::{
+    self, Key, Response, ScrollArea,
+    Sense, Ui
+};
+use mk_core::workspace::{Entry, Workspace};
+
+pub enum TreeAction {
+    Open(PathBuf),
+    CreateFile(PathBuf),
+    CreateDir(PathBuf),
+    Rename(PathBuf, PathBuf),
+    Delete(PathBuf),
+    ToggleFilter
+}
+
+enum Edit {
+    NewFile(PathBuf, String),
+    NewDir(PathBuf, String),
+    Rename(PathBuf, String)
+}
+
+#[derive(Default)]
+pub struct FileTree {
+    edit: Option<Edit>,
+    deleting: Option<PathBuf>
+}
+
+impl FileTree {
+    pub fn ui(
+        &mut self,
+        ui: &mut Ui,
+        workspace: &Workspace,
+        open: Option<&Path>
+    ) -> Option<TreeAction> {
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            let root = ui.add(
+                egui::Label::new(egui::RichText::new(workspace.name()).strong())
+                    .sense(Sense::click())
+            );
+            self.entry_menu(&root, workspace.root(), true);
+            drop_target(&root, workspace.root(), &mut action);
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let filter = if workspace.markdown_only { "Markdown" } else { "All files" };
+                if ui.small_button(filter).on_hover_text("Toggle which files are listed").clicked() {
+                    action = Some(TreeAction::ToggleFilter);
+                }
+            });
+        });
+        ui.separator();
+
+        ScrollArea::vertical()
+            .id_salt("file tree")
+            .show(ui, |ui| {
+                self.new_entry_ui(ui, workspace.root(), &mut action);
+                self.entries_ui(ui, &workspace.entries, open, &mut action);
+            });
+
+        self.delete_window(ui.ctx(), &mut action);
+        action
+    }
+
+    fn entries_ui(
+        &mut self,
+        ui: &mut Ui,
+        entries: &[Entry],
+        open: Option<&Path>,
+        action: &mut Option<TreeAction>
+    ) {
+        for entry in entries {
+            if self.rename_ui(ui, &entry.path, action) {
+                continue;
+            }
+
+            match &entry.children {
+                Some(children) => {
+                    let response = egui::CollapsingHeader::new(&entry.name)
+                        .id_salt(&entry.path)
+                        .default_open(open.is_some_and(|open| open.starts_with(&entry.path)))
+                        .show(ui, |ui| {
+                            self.new_entry_ui(ui, &entry.path, action);
+                            self.entries_ui(ui, children, open, action);
+                        });
+                    let header = response.header_response.interact(Sense::drag());
+                    header.dnd_set_drag_payload(entry.path.clone());
+                    self.entry_menu(&header, &entry.path, true);
+                    drop_target(&header, &entry.path, action);
+                }
+                None => {
+                    let selected = open == Some(entry.path.as_path());
+                    let response = ui
+                        .selectable_label(selected, &entry.name)
+                        .interact(Sense::drag());
+                    if response.clicked() {
+                        *action = Some(TreeAction::Open(entry.path.clone()));
+                    }
+                    response.dnd_set_drag_payload(entry.path.clone());
+                    self.entry_menu(&response, &entry.path, false);
+                }
+            }
+        }
+    }
+
+    fn entry_menu(&mut self, response: &Response, path: &Path, is_dir: bool) {
+        response.context_menu(|ui| {
+            if is_dir {
+                if ui.button("New note").clicked() {
+                    self.edit = Some(Edit::NewFile(path.to_path_buf(), "Untitled.md".to_string()));
+                    ui.close_menu();
+                }
+                if ui.button("New folder").clicked() {
+                    self.edit = Some(Edit::NewDir(path.to_path_buf(), String::new()));
+                    ui.close_menu();
+                }
+            }
+            if path.parent().is_some() {
+                if ui.button("Rename").clicked() {
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    self.edit = Some(Edit::Rename(path.to_path_buf(), name));
+                    ui.close_menu();
+                }
+                if ui.button("Delete").clicked() {
+                    self.deleting = Some(path.to_path_buf());
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+
+    /// Shows the name field for a file or folder being created in `dir`.
+    fn new_entry_ui(&mut self, ui: &mut Ui, dir: &Path, action: &mut Option<TreeAction>) {
+        let (parent, name, is_dir) = match &mut self.edit {
+            Some(Edit::NewFile(parent, name)) => (parent, name, false),
+            Some(Edit::NewDir(parent, name)) => (parent, name, true),
+            _ => return
+        };
+        if parent != dir {
+            return;
+        }
+
+        match name_field(ui, name) {
+            Some(true) if !name.is_empty() => {
+                let path = dir.join(name.as_str());
+                *action = Some(if is_dir {
+                    TreeAction::CreateDir(path)
+                } else {
+                    TreeAction::CreateFile(path)
+                });
+                self.edit = None;
+            }
+            Some(_) => self.edit = None,
+            None => {}
+        }
+    }
+
+    /// Shows the name field in place of `path` while it is being renamed.
+    fn rename_ui(&mut self, ui: &mut Ui, path: &Path, action: &mut Option<TreeAction>) -> bool {
+        let Some(Edit::Rename(renaming, name)) = &mut self.edit else {
+            return false;
+        };
+        if renaming != path {
+            return false;
+        }
+
+        match name_field(ui, name) {
+            Some(true) if !name.is_empty() => {
+                let to = path.with_file_name(name.as_str());
+                *action = Some(TreeAction::Rename(path.to_path_buf(), to));
+                self.edit = None;
+            }
+            Some(_) => self.edit = None,
+            None => {}
+        }
+        true
+    }
+
+    fn delete_window(&mut self, ctx: &egui::Context, action: &mut Option<TreeAction>) {
+        let Some(path) = &self.deleting else {
+            return;
+        };
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Delete")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Delete {} permanently?", path.display()));
+                ui.horizontal(|ui| {
+                    confirmed = ui.button("Delete").clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+
+        if confirmed {
+            *action = self.deleting.take().map(TreeAction::Delete);
+        } else if cancelled {
+            self.deleting = None;
+        }
+    }
+}
+
+/// A focused single line field. `Some(true)` once Enter confirms the name,
+/// `Some(false)` if the user clicked away or pressed Escape.
+fn name_field(ui: &mut Ui, name: &mut String) -> Option<bool> {
+    let response = ui.text_edit_singleline(name);
+    if !response.has_focus() && !response.lost_focus() {
+        response.request_focus();
+    }
+    if response.lost_focus() {
+        return Some(ui.input(|i| i.key_pressed(Key::Enter)));
+    }
+    None
+}
+
+fn drop_target(response: &Response, dir: &Path, action: &mut Option<TreeAction>) {
+    if let Some(dragged) = response.dnd_release_payload::<PathBuf>() {
+        if let Some(name) = dragged.file_name() {
+            let to = dir.join(name);
+            if to != *dragged {
+                *action = Some(TreeAction::Rename(dragged.to_path_buf(), to));
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::sync::Arc;
+use mk_core::eframe::egui::{
+    pos2, text::LayoutJob, vec2, Color32, FontId,
+    Galley, Painter, Pos2, Response, Sense,
+    Shape, Stroke, TextFormat, TextStyle,
+    Ui, Vec2
+};
+use mk_core::math::{self, Node};
+
+/// Lays out `tex` in the flow of the text around it.
+pub fn inline(ui: &mut Ui, tex: &str) -> Response {
+    let size = TextStyle::Body.resolve(ui.style()).size;
+    let math = layout(ui, &math::parse(tex), size);
+    let (rect, response) = ui.allocate_exact_size(math.size(), Sense::hover());
+    math.paint(ui.painter(), rect.min + vec2(0.0, math.ascent), ui.visuals().text_color());
+    response.on_hover_text(tex)
+}
+
+/// Lays out `tex` larger, centered on a row of its own.
+pub fn display(ui: &mut Ui, tex: &str) -> Response {
+    let size = TextStyle::Body.resolve(ui.style()).size * 1.2;
+    let math = layout(ui, &math::parse(tex), size);
+    let padding = size * 0.3;
+    let desired = vec2(ui.available_size_before_wrap().x, math.size().y + 2.0 * padding);
+    let (rect, response) = ui.allocate_exact_size(desired, Sense::hover());
+    let origin = pos2(rect.center().x - math.width / 2.0, rect.top() + padding + math.ascent);
+    math.paint(ui.painter(), origin, ui.visuals().text_color());
+    response.on_hover_text(tex)
+}
+
+/// Pieces of laid out math, positioned relative to the left end of its
+/// baseline.
+#[derive(Default)]
+struct MathBox {
+    width: f32,
+    /// Height above the baseline.
+    ascent: f32,
+    /// Depth below the baseline.
+    descent: f32,
+    pieces: Vec<Piece>
+}
+
+enum Piece {
+    /// Text with its top left corner at the given position.
+    Text(Pos2, Arc<Galley>),
+    /// A line through the points, and its width.
+    Line(Vec<Pos2>, f32)
+}
+
+impl MathBox {
+    fn size(&self) -> Vec2 {
+        vec2(self.width.max(0.0), self.ascent + self.descent)
+    }
+
+    /// Adds `other` with its baseline starting at `offset` from ours.
+    fn add(&mut self, other: MathBox, offset: Vec2) {
+        self.width = self.width.max(offset.x + other.width);
+        self.ascent = self.ascent.max(other.ascent - offset.y);
+        self.descent = self.descent.max(other.descent + offset.y);
+        self.pieces.extend(other.pieces.into_iter().map(|piece| match piece {
+            Piece::Text(pos, galley) => Piece::Text(pos + offset, galley),
+            Piece::Line(points, width) => {
+                Piece::Line(points.into_iter().map(|point| point + offset).collect(), width)
+            }
+        }));
+    }
+
+    /// Adds `other` after everything so far, on the same baseline.
+    fn append(&mut self, other: MathBox) {
+        let x = self.width;
+        let width = other.width;
+        self.add(other, vec2(x, 0.0));
+        self.width = x + width;
+    }
+
+    /// Moves everything right so it sits in the middle of `width`.
+    fn centered(self, width: f32) -> MathBox {
+        let mut centered = MathBox::default();
+        let offset = (width - self.width) / 2.0;
+        centered.add(self, vec2(offset, 0.0));
+        centered.width = width;
+        centered
+    }
+
+    fn paint(&self, painter: &Painter, origin: Pos2, color: Color32) {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(pos, galley) => {
+                    painter.galley(origin + pos.to_vec2(), galley.clone(), color);
+                }
+                Piece::Line(points, width) => {
+                    let points = points.iter().map(|point| origin + point.to_vec2()).collect();
+                    painter.add(Shape::line(points, Stroke::new(*width, color)));
+                }
+            }
+        }
+    }
+}
+
+fn layout(ui: &Ui, nodes: &[Node], size: f32) -> MathBox {
+    let mut row = MathBox::default();
+    for node in nodes {
+        row.append(node_box(ui, node, size));
+    }
+    row
+}
+
+fn node_box(ui: &Ui, node: &Node, size: f32) -> MathBox {
+    let script_size = (size * 0.7).max(7.0);
+    match node {
+        Node::Symbol(c) => text(ui, c.encode_utf8(&mut [0; 4]), size, c.is_alphabetic()),
+        Node::Operator(c) => {
+            let space = size * 0.22;
+            let mut row = MathBox {
+                width: space,
+                ..Default::default()
+            };
+            row.append(text(ui, c.encode_utf8(&mut [0; 4]), size, false));
+            row.width += space;
+            row
+        }
+        Node::Large(c) => text(ui, c.encode_utf8(&mut [0; 4]), size * 1.4, false),
+        Node::Text(name) => text(ui, name, size, false),
+        Node::Space(ems) => MathBox {
+            width: ems * size,
+            ..Default::default()
+        },
+        Node::Group(nodes) => layout(ui, nodes, size),
+        Node::Fraction(numerator, denominator) => {
+            let part_size = (size * 0.85).max(7.0);
+            let numerator = layout(ui, numerator, part_size);
+            let denominator = layout(ui, denominator, part_size);
+            let width = numerator.width.max(denominator.width) + size * 0.2;
+            // The bar sits on the math axis, around the middle of an `=`.
+            let axis = size * 0.3;
+            let gap = size * 0.12;
+            let thickness = (size / 16.0).max(1.0);
+
+            let mut fraction = MathBox {
+                width,
+                ..Default::default()
+            };
+            let up = -axis - gap - numerator.descent;
+            let down = -axis + gap + denominator.ascent;
+            fraction.add(numerator.centered(width), vec2(0.0, up));
+            fraction.add(denominator.centered(width), vec2(0.0, down));
+            fraction.pieces.push(Piece::Line(vec![pos2(0.0, -axis), pos2(width, -axis)], thickness));
+            fraction
+        }
+        Node::Root(radicand) => {
+            let radicand = layout(ui, radicand, size);
+            let sign = size * 0.55;
+            let gap = size * 0.15;
+            let thickness = (size / 16.0).max(1.0);
+            let top = -radicand.ascent - gap;
+            let bottom = radicand.descent;
+            let width = sign + radicand.width + size * 0.1;
+
+            let mut root = MathBox {
+                width,
+                ascent: -top + thickness,
+                descent: bottom,
+                ..Default::default()
+            };
+            root.pieces.push(Piece::Line(vec![
+                pos2(0.0, bottom - (bottom - top) * 0.4),
+                pos2(sign * 0.3, bottom - (bottom - top) * 0.5),
+                pos2(sign * 0.6, bottom),
+                pos2(sign, top),
+                pos2(width, top)
+            ], thickness));
+            root.add(radicand, vec2(sign + size * 0.05, 0.0));
+            root
+        }
+        Node::Scripts { base, sub, sup } => {
+            let mut scripts = node_box(ui, base, size);
+            let x = scripts.width;
+            let mut width: f32 = 0.0;
+            if let Some(sup) = sup {
+                let sup = layout(ui, sup, script_size);
+                width = width.max(sup.width);
+                let raise = (scripts.ascent - sup.ascent * 0.6).max(size * 0.35);
+                scripts.add(sup, vec2(x, -raise));
+            }
+            if let Some(sub) = sub {
+                let sub = layout(ui, sub, script_size);
+                width = width.max(sub.width);
+                let drop = (scripts.descent + sub.ascent * 0.4).max(size * 0.2);
+                scripts.add(sub, vec2(x, drop));
+            }
+            scripts.width = x + width;
+            scripts
+        }
+    }
+}
+
+fn text(ui: &Ui, text: &str, size: f32, italics: bool) -> MathBox {
+    let format = TextFormat {
+        font_id: FontId::proportional(size),
+        color: ui.visuals().text_color(),
+        italics,
+        ..Default::default()
+    };
+    let galley = ui.fonts(|fonts| fonts.layout_job(LayoutJob::single_section(text.to_string(), format)));
+    let baseline = galley.rows
+        .first()
+        .and_then(|row| row.glyphs.first())
+        .map_or(size * 0.8, |glyph| glyph.pos.y);
+    MathBox {
+        width: galley.size().x,
+        ascent: baseline,
+        descent: galley.size().y - baseline,
+        pieces: vec![Piece::Text(pos2(0.0, -baseline), galley)]
+    }
+}
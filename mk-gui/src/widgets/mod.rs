@@ -1,4 +1,17 @@
+mod backlinks;
+mod command_palette;
 mod editor;
+mod emoji_picker;
+mod file_tree;
+mod math;
+mod quick_open;
+mod search;
+pub mod diff;
 pub mod viewer;
 
+pub use backlinks::Backlinks;
+pub use command_palette::{Command, CommandPalette};
 pub use editor::Editor;
+pub use file_tree::{FileTree, TreeAction};
+pub use quick_open::QuickOpen;
+pub use search::SearchPanel;
@@ -0,0 +1,212 @@
+use std::path::{Path, PathBuf};
+use mk_core::eframe::egui::{
+    self, text::LayoutJob, Align2,
+    Context, Key, Modifiers, ScrollArea,
+    TextFormat, TextStyle, Ui
+};
+use mk_core::fuzzy::fuzzy_match;
+use mk_core::search::Index;
+
+const MAX_RESULTS: usize = 50;
+/// How much being the most recently used document is worth, compared to
+/// the fuzzy score of a single matched character.
+const RECENT_BONUS: i64 = 64;
+
+struct Candidate {
+    path: PathBuf,
+    /// What the query is matched against.
+    text: String,
+    /// Shown dimmed after the text, the file a heading belongs to.
+    detail: Option<String>,
+    line: Option<usize>
+}
+
+pub struct Selection {
+    pub path: PathBuf,
+    pub line: Option<usize>
+}
+
+pub struct QuickOpen {
+    root: PathBuf,
+    query: String,
+    selected: usize,
+    candidates: Vec<Candidate>,
+    /// The index generation the heading candidates were taken from.
+    generation: Option<u64>
+}
+
+impl QuickOpen {
+    pub fn new(root: &Path, files: &[&Path]) -> Self {
+        let candidates = files
+            .iter()
+            .map(|path| Candidate {
+                path: path.to_path_buf(),
+                text: relative(root, path),
+                detail: None,
+                line: None
+            })
+            .collect();
+
+        QuickOpen {
+            root: root.to_path_buf(),
+            query: String::new(),
+            selected: 0,
+            candidates,
+            generation: None
+        }
+    }
+
+    /// Takes the headings from `index`, once it has read every file.
+    fn update_headings(&mut self, index: &Index) {
+        let generation = index.generation();
+        if self.generation == Some(generation) || (self.generation.is_some() && index.is_indexing()) {
+            return;
+        }
+        self.generation = Some(generation);
+
+        self.candidates.retain(|candidate| candidate.line.is_none());
+        let headings = index.headings().into_iter().map(|(path, heading)| Candidate {
+            detail: Some(relative(&self.root, &path)),
+            path,
+            text: heading.text,
+            line: Some(heading.line)
+        });
+        self.candidates.extend(headings);
+    }
+
+    /// `recent` lists documents by how recently they were used, most recent
+    /// first. Returns the picked entry, and clears `open` once done.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        index: &Index,
+        recent: &[PathBuf],
+        open: &mut bool
+    ) -> Option<Selection> {
+        self.update_headings(index);
+        let results = self.results(recent);
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| (
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+            i.consume_key(Modifiers::NONE, Key::Enter),
+            i.consume_key(Modifiers::NONE, Key::Escape)
+        ));
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+
+        let mut picked = enter.then_some(self.selected).filter(|_| !results.is_empty());
+        let mut query_changed = false;
+
+        egui::Window::new("Quick open")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+            .fixed_size([480.0, 0.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Go to file or heading")
+                        .desired_width(f32::INFINITY)
+                );
+                response.request_focus();
+                query_changed = response.changed();
+
+                ui.separator();
+                ScrollArea::vertical()
+                    .max_height(360.0)
+                    .show(ui, |ui| {
+                        for (index, (candidate, positions)) in results.iter().enumerate() {
+                            let selected = index == self.selected;
+                            let job = result_job(ui, &self.candidates[*candidate], positions);
+                            let response = ui.selectable_label(selected, job);
+                            if selected && (up || down) {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                picked = Some(index);
+                            }
+                        }
+                        if results.is_empty() {
+                            ui.weak("No matches");
+                        }
+                    });
+            });
+
+        if query_changed {
+            self.selected = 0;
+        }
+        if escape || picked.is_some() {
+            *open = false;
+        }
+
+        let candidate = &self.candidates[results.get(picked?)?.0];
+        Some(Selection {
+            path: candidate.path.clone(),
+            line: candidate.line
+        })
+    }
+
+    /// Indices of the matching candidates, best first, and the matched chars.
+    fn results(&self, recent: &[PathBuf]) -> Vec<(usize, Vec<usize>)> {
+        let mut results = self.candidates
+            .iter()
+            .enumerate()
+            // Every file has a handful of headings; only list them once the
+            // user is searching for something.
+            .filter(|(_, candidate)| !self.query.trim().is_empty() || candidate.line.is_none())
+            .filter_map(|(index, candidate)| {
+                let found = fuzzy_match(&self.query, &candidate.text)?;
+                let recency = recent
+                    .iter()
+                    .position(|path| *path == candidate.path)
+                    .map_or(0, |index| RECENT_BONUS / (index as i64 + 1));
+                Some((found.score + recency, index, found.positions))
+            })
+            .collect::<Vec<_>>();
+
+        results.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then_with(|| self.candidates[a.1].text.cmp(&self.candidates[b.1].text))
+        });
+        results
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, index, positions)| (index, positions))
+            .collect()
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).display().to_string()
+}
+
+fn result_job(ui: &Ui, candidate: &Candidate, positions: &[usize]) -> LayoutJob {
+    let font_id = TextStyle::Body.resolve(ui.style());
+    let normal = TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+    let matched = TextFormat::simple(font_id.clone(), ui.visuals().strong_text_color());
+    let weak = TextFormat::simple(font_id, ui.visuals().weak_text_color());
+
+    let mut job = LayoutJob::default();
+    if candidate.line.is_some() {
+        job.append("# ", 0.0, weak.clone());
+    }
+    for (index, c) in candidate.text.chars().enumerate() {
+        let format = if positions.contains(&index) {
+            matched.clone()
+        } else {
+            normal.clone()
+        };
+        job.append(c.encode_utf8(&mut [0; 4]), 0.0, format);
+    }
+    if let Some(detail) = &candidate.detail {
+        job.append(detail, 12.0, weak);
+    }
+    job
+}
@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+use mk_core::eframe::egui::{
+    self, text::LayoutJob, ScrollArea,
+    TextFormat, TextStyle, Ui
+};
+use mk_core::search::{FileMatches, Index, LineMatch, Query};
+
+#[derive(Default)]
+pub struct SearchPanel {
+    query: Query,
+    results: Vec<FileMatches>,
+    error: Option<String>,
+    searched: Option<(Query, u64)>,
+    focus: bool
+}
+
+impl SearchPanel {
+    pub fn focus(&mut self) {
+        self.focus = true;
+    }
+
+    /// Returns the file and zero based line of a clicked match.
+    pub fn ui(&mut self, ui: &mut Ui, index: &Index, root: &Path) -> Option<(PathBuf, usize)> {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.query.text)
+                .hint_text("Search notes")
+                .desired_width(f32::INFINITY)
+        );
+        if std::mem::take(&mut self.focus) {
+            response.request_focus();
+        }
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.query.case_sensitive, "Aa")
+                .on_hover_text("Match case");
+            ui.toggle_value(&mut self.query.regex, ".*")
+                .on_hover_text("Regular expression");
+            if index.is_indexing() {
+                ui.spinner();
+                ui.weak("Indexing…");
+            }
+        });
+        ui.separator();
+
+        let searched = (self.query.clone(), index.generation());
+        if self.searched.as_ref() != Some(&searched) {
+            match index.search(&self.query) {
+                Ok(results) => {
+                    self.results = results;
+                    self.error = None;
+                }
+                Err(error) => {
+                    self.results.clear();
+                    self.error = Some(error.to_string());
+                }
+            }
+            self.searched = Some(searched);
+        }
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+            return None;
+        }
+        if self.results.is_empty() {
+            if !self.query.text.is_empty() {
+                ui.weak("No results");
+            }
+            return None;
+        }
+
+        let mut clicked = None;
+        ScrollArea::vertical()
+            .id_salt("search results")
+            .show(ui, |ui| {
+                for file in &self.results {
+                    let name = file.path
+                        .strip_prefix(root)
+                        .unwrap_or(&file.path)
+                        .display()
+                        .to_string();
+                    egui::CollapsingHeader::new(format!("{name} ({})", file.lines.len()))
+                        .id_salt(&file.path)
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for found in &file.lines {
+                                if ui.selectable_label(false, match_job(ui, found)).clicked() {
+                                    clicked = Some((file.path.clone(), found.line));
+                                }
+                            }
+                        });
+                }
+            });
+        clicked
+    }
+}
+
+fn match_job(ui: &Ui, found: &LineMatch) -> LayoutJob {
+    let font_id = TextStyle::Body.resolve(ui.style());
+    let weak = TextFormat::simple(font_id.clone(), ui.visuals().weak_text_color());
+    let normal = TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+    let highlighted = TextFormat {
+        background: ui.visuals().selection.bg_fill,
+        color: ui.visuals().strong_text_color(),
+        ..TextFormat::simple(font_id, ui.visuals().text_color())
+    };
+
+    let mut job = LayoutJob::default();
+    job.append(&format!("{}  ", found.line + 1), 0.0, weak);
+
+    // Skip the indentation so matches deep in a nested list stay visible.
+    let text = found.text.trim_start();
+    let skipped = found.text.len() - text.len();
+    let mut at = skipped;
+    for range in &found.ranges {
+        let start = range.start.max(at);
+        job.append(&found.text[at..start], 0.0, normal.clone());
+        job.append(&found.text[start..range.end.max(start)], 0.0, highlighted.clone());
+        at = range.end.max(start);
+    }
+    job.append(&found.text[at..], 0.0, normal);
+    job
+}
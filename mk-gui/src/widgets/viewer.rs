@@ -1,19 +1,182 @@
-use mk_core::parser;
+use std::{collections::HashMap, sync::Arc};
+use mk_core::{front_matter::{self, FrontMatter, Value}, parser::{self, CalloutKind}, workspace, HIGHLIGHT};
+use super::math;
 use mk_core::eframe::egui::{
     vec2, Align, Align2, Layout,
-    Hyperlink, Response, Sense,
-    RichText, Separator, Shape,
-    TextStyle, Rounding, Stroke, Ui
+    CollapsingHeader, Context, CursorIcon,
+    Color32, Frame, Grid, Hyperlink, Id, Margin,
+    Label, Rect, Response, Sense, RichText,
+    layers::ShapeIdx, Separator, Shape, TextStyle,
+    Rounding, Stroke, Ui
 };
 
-pub fn mark(ui: &mut Ui, mark: &str) {
-    mark_it(ui, parser::Parser::new(mark));
+/// Memory key for the footnote whose marker was clicked, so the footnotes
+/// section scrolls to it.
+const FOOTNOTE_TARGET: &str = "footnote target";
+/// Memory key for the index of the heading to scroll to.
+const HEADING_TARGET: &str = "heading target";
+/// Memory key for the `Extensions` every view uses.
+const EXTENSIONS: &str = "extensions";
+/// Memory key for the options of the document being rendered, so footnote
+/// previews read their text the same way.
+const OPTIONS: &str = "parser options";
+
+/// A link in the rendered view that the app follows itself.
+#[derive(Clone, Debug)]
+pub enum Link {
+    /// `[[Note]]`, by the name of the note.
+    Wiki(String),
+    /// `[text](#slug)`, a heading in the same document.
+    Fragment(String),
+    /// `[text](other.md)`, a Markdown file relative to this one.
+    File(String)
+}
+
+/// Draws the `Item::Custom` items of one kind, given their style and text.
+type Render = dyn Fn(&mut Ui, parser::Style, &str) + Send + Sync;
+
+/// Parser extensions, and how the viewer draws the items they read.
+/// Items of a kind with no renderer show as plain text.
+#[derive(Default)]
+pub struct Extensions {
+    parsers: Vec<Box<dyn parser::Extension>>,
+    renderers: HashMap<&'static str, Box<Render>>
+}
+
+impl Extensions {
+    pub fn add(&mut self, extension: impl parser::Extension + 'static) -> &mut Self {
+        self.parsers.push(Box::new(extension));
+        self
+    }
+
+    pub fn render(
+        &mut self,
+        kind: &'static str,
+        render: impl Fn(&mut Ui, parser::Style, &str) + Send + Sync + 'static
+    ) -> &mut Self {
+        self.renderers.insert(kind, Box::new(render));
+        self
+    }
+
+    /// Makes every rendered view in `ctx` use these extensions.
+    pub fn install(self, ctx: &Context) {
+        ctx.memory_mut(|memory| memory.data.insert_temp(Id::new(EXTENSIONS), Arc::new(self)));
+    }
+
+    fn get(ctx: &Context) -> Arc<Extensions> {
+        ctx.memory_mut(|memory| memory.data.get_temp(Id::new(EXTENSIONS))).unwrap_or_default()
+    }
+}
+
+/// Scrolls the next rendered view to the heading at `index` in the order
+/// `parser::headings` lists them.
+pub fn scroll_to_heading(ctx: &Context, index: usize) {
+    ctx.memory_mut(|memory| memory.data.insert_temp(Id::new(HEADING_TARGET), index));
+}
+
+/// Renders `mark`, returning the link clicked, if any.
+pub fn mark(ui: &mut Ui, mark: &str, options: parser::ParserOptions) -> Option<Link> {
+    if let Some(front) = front_matter::parse(mark) {
+        metadata_card(ui, &front);
+    }
+    ui.memory_mut(|memory| memory.data.insert_temp(Id::new(OPTIONS), options));
+    let extensions = Extensions::get(ui.ctx());
+    let mut parser = parser::Parser::with_options(mark, options).with_extensions(&extensions.parsers);
+    let target = ui.memory_mut(|memory| memory.data.remove_temp::<usize>(Id::new(HEADING_TARGET)));
+    let mut clicked = render(ui, &mut parser, target);
+
+    let footnotes = parser.footnotes().collect::<Vec<_>>();
+    if !footnotes.is_empty() {
+        ui.separator();
+        let target = ui.memory_mut(|memory| memory.data.remove_temp::<usize>(Id::new(FOOTNOTE_TARGET)));
+        for (index, text) in footnotes.into_iter().enumerate() {
+            let number = index + 1;
+            let response = ui.horizontal_top(|ui| {
+                ui.label(RichText::new(format!("{number}.")).small().strong());
+                let footnote = parser::Parser::with_options(text, options).with_extensions(&extensions.parsers);
+                clicked = mark_it(ui, footnote).or(clicked.take());
+            }).response;
+            if target == Some(number) {
+                response.scroll_to_me(Some(Align::Center));
+            }
+        }
+    }
+
+    if !parser.unresolved().is_empty() {
+        let labels = parser
+            .unresolved()
+            .iter()
+            .map(|label| format!("[{label}]"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        ui.separator();
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("Undefined link references: {labels}")
+        );
+    }
+
+    clicked
+}
+
+/// Title, date and tags from the front matter, with any other fields
+/// folded away underneath.
+fn metadata_card(ui: &mut Ui, front: &FrontMatter<'_>) {
+    Frame::group(ui.style()).show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        if let Some(title) = front.title() {
+            ui.label(RichText::new(title).heading());
+        }
+        if let Some(date) = front.date() {
+            ui.weak(date);
+        }
+
+        let tags = front.tags();
+        if !tags.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                for tag in tags {
+                    Frame::none()
+                        .fill(ui.visuals().faint_bg_color)
+                        .rounding(Rounding::same(8.0))
+                        .inner_margin(Margin::symmetric(6.0, 1.0))
+                        .show(ui, |ui| ui.label(RichText::new(format!("#{tag}")).small()));
+                }
+            });
+        }
+
+        let others = front.others().collect::<Vec<_>>();
+        if !others.is_empty() {
+            CollapsingHeader::new(RichText::new(format!("{} more", others.len())).small())
+                .id_salt("front matter")
+                .show(ui, |ui| {
+                    Grid::new("front matter fields").num_columns(2).show(ui, |ui| {
+                        for (name, value) in others {
+                            ui.weak(*name);
+                            match value {
+                                Value::Text(text) => ui.label(*text),
+                                Value::List(items) => ui.label(items.join(", "))
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+        }
+    });
+    ui.add_space(ui.spacing().item_spacing.y);
 }
 
 pub fn mark_it<'em>(
     ui: &mut Ui, 
     items: impl Iterator<Item = parser::Item<'em>>
-) {
+) -> Option<Link> {
+    render(ui, items, None)
+}
+
+fn render<'em>(
+    ui: &mut Ui,
+    items: impl Iterator<Item = parser::Item<'em>>,
+    target: Option<usize>
+) -> Option<Link> {
     let initial_size = vec2(
         ui.available_width(),
         ui.spacing().interact_size.y
@@ -25,13 +188,248 @@ pub fn mark_it<'em>(
         let row_height = ui.text_style_height(&TextStyle::Body);
         ui.set_row_height(row_height);
 
-        for item in items {
-            item_ui(ui, item);
+        let mut clicked = None;
+        let mut headings = Headings {
+            target,
+            ..Default::default()
+        };
+        let mut quotes = QuoteBars::default();
+        let mut prefixed = false;
+        let mut callouts = 0;
+        // Quote depth of a folded callout whose body is being skipped.
+        let mut folded = None;
+        let mut items = items.peekable();
+        while let Some(item) = items.next() {
+            headings.see(ui, &item);
+            match item {
+                parser::Item::QuoteIndent => {
+                    quotes.indent(ui);
+                    prefixed = true;
+                }
+                parser::Item::Newline => {
+                    quotes.end_line(ui);
+                    clicked = item_ui(ui, item).or(clicked.take());
+                    prefixed = false;
+                    let Some(depth) = folded.take() else {
+                        continue;
+                    };
+                    // Drop the body, keeping the quote markers of the first
+                    // line past it.
+                    loop {
+                        let mut indents = 0;
+                        while items.next_if_eq(&parser::Item::QuoteIndent).is_some() {
+                            indents += 1;
+                        }
+                        if indents < depth {
+                            for _ in 0..indents {
+                                quotes.indent(ui);
+                            }
+                            prefixed = indents > 0;
+                            break;
+                        }
+                        for item in items.by_ref() {
+                            headings.see(ui, &item);
+                            if item == parser::Item::Newline {
+                                break;
+                            }
+                        }
+                    }
+                }
+                parser::Item::Callout(kind, open, title) => {
+                    quotes.callout(ui, callout_color(kind));
+                    let mut shown = None;
+                    if let Some(default) = open {
+                        let id = ui.id().with(("callout", callouts));
+                        let mut open = ui.data(|data| data.get_temp(id)).unwrap_or(default);
+                        callout_title(ui, kind, title, Some(&mut open));
+                        ui.data_mut(|data| data.insert_temp(id, open));
+                        shown = Some(open);
+                    } else {
+                        callout_title(ui, kind, title, None);
+                    }
+                    if shown == Some(false) {
+                        folded = Some(quotes.depth);
+                    }
+                    callouts += 1;
+                }
+                parser::Item::Indentation(_)
+                | parser::Item::BulletPoint(_)
+                | parser::Item::NumberedPoint(..)
+                | parser::Item::Definition => {
+                    clicked = item_ui(ui, item).or(clicked.take());
+                    prefixed = true;
+                }
+                _ if prefixed => {
+                    // Lay out the rest of the line on its own, so wrapped
+                    // rows line up under the text instead of the margin.
+                    let size = vec2(ui.available_size_before_wrap().x, row_height);
+                    ui.allocate_ui_with_layout(size, layout, |ui| {
+                        ui.spacing_mut().item_spacing.x = 0.0;
+                        ui.set_row_height(row_height);
+                        clicked = item_ui(ui, item).or(clicked.take());
+                        while let Some(item) = items.next_if(|item| *item != parser::Item::Newline) {
+                            headings.see(ui, &item);
+                            clicked = item_ui(ui, item).or(clicked.take());
+                        }
+                    });
+                }
+                _ => clicked = item_ui(ui, item).or(clicked.take())
+            }
         }
-    });
+        quotes.end_line(ui);
+        quotes.close(ui, 0);
+        clicked
+    }).inner
+}
+
+/// Counts heading lines as they render, to scroll to the target one.
+#[derive(Default)]
+struct Headings {
+    target: Option<usize>,
+    seen: usize,
+    in_heading: bool
+}
+
+impl Headings {
+    fn see(&mut self, ui: &mut Ui, item: &parser::Item) {
+        let style = match item {
+            parser::Item::Newline => {
+                self.in_heading = false;
+                return;
+            }
+            parser::Item::Text(style, _)
+            | parser::Item::Hyperlink(style, ..)
+            | parser::Item::WikiLink(style, ..)
+            | parser::Item::Emoji(style, ..)
+            | parser::Item::Abbreviation(style, ..) => style,
+            _ => return
+        };
+        if self.in_heading || !(style.heading || style.subheading) {
+            return;
+        }
+
+        self.in_heading = true;
+        if self.target == Some(self.seen) {
+            let row_height = ui.text_style_height(&TextStyle::Body);
+            let rect = Rect::from_min_size(ui.cursor().min, vec2(1.0, row_height));
+            ui.scroll_to_rect(rect, Some(Align::TOP));
+        }
+        self.seen += 1;
+    }
+}
+
+/// Draws one unbroken bar per quote level, spanning all the consecutive
+/// lines quoted at least that deep.
+#[derive(Default)]
+struct QuoteBars {
+    bars: Vec<QuoteBar>,
+    depth: usize
+}
+
+struct QuoteBar {
+    x: f32,
+    top: f32,
+    bottom: f32,
+    /// Color and background shape of a callout, tinted once its size is
+    /// known.
+    callout: Option<(Color32, ShapeIdx)>
 }
 
-pub fn item_ui(ui: &mut Ui, item: parser::Item<'_>) {
+impl QuoteBars {
+    fn indent(&mut self, ui: &mut Ui) {
+        let row_height = ui.text_style_height(&TextStyle::Body);
+        let rect = ui
+            .allocate_exact_size(vec2(row_height, row_height), Sense::hover())
+            .0;
+        if self.depth == self.bars.len() {
+            self.bars.push(QuoteBar {
+                x: rect.center().x,
+                top: rect.top(),
+                bottom: rect.bottom(),
+                callout: None
+            });
+        }
+        self.depth += 1;
+    }
+
+    /// Turns the innermost open quote into a callout.
+    fn callout(&mut self, ui: &mut Ui, color: Color32) {
+        if let Some(bar) = self.bars.get_mut(self.depth.wrapping_sub(1)) {
+            bar.callout = Some((color, ui.painter().add(Shape::Noop)));
+        }
+    }
+
+    fn end_line(&mut self, ui: &mut Ui) {
+        self.close(ui, self.depth);
+        let bottom = ui.min_rect().bottom();
+        for bar in &mut self.bars {
+            bar.bottom = bottom;
+        }
+        self.depth = 0;
+    }
+
+    fn close(&mut self, ui: &mut Ui, depth: usize) {
+        let weak = ui.visuals().weak_text_color();
+        let right = ui.max_rect().right();
+        for bar in self.bars.drain(depth.min(self.bars.len())..) {
+            let Some((color, background)) = bar.callout else {
+                ui.painter().vline(bar.x, bar.top..=bar.bottom, (1.0, weak));
+                continue;
+            };
+            let rect = Rect::from_x_y_ranges(bar.x..=right, bar.top..=bar.bottom);
+            let tint = color.gamma_multiply(0.12);
+            ui.painter().set(background, Shape::rect_filled(rect, Rounding::same(4.0), tint));
+            ui.painter().vline(bar.x, bar.top..=bar.bottom, (2.0, color));
+        }
+    }
+}
+
+fn callout_color(kind: CalloutKind) -> Color32 {
+    match kind {
+        CalloutKind::Note => Color32::from_rgb(47, 129, 247),
+        CalloutKind::Tip => Color32::from_rgb(63, 185, 80),
+        CalloutKind::Important => Color32::from_rgb(163, 113, 247),
+        CalloutKind::Warning => Color32::from_rgb(210, 153, 34),
+        CalloutKind::Caution => Color32::from_rgb(248, 81, 73)
+    }
+}
+
+/// The icon and title of a callout. Clicking it flips `open` for callouts
+/// that fold.
+fn callout_title(ui: &mut Ui, kind: CalloutKind, title: &str, open: Option<&mut bool>) {
+    let icon = match kind {
+        CalloutKind::Note => "ℹ",
+        CalloutKind::Tip => "💡",
+        CalloutKind::Important => "❗",
+        CalloutKind::Warning => "⚠",
+        CalloutKind::Caution => "🛑"
+    };
+    let title = if title.is_empty() { kind.title() } else { title };
+    let arrow = match open.as_deref() {
+        Some(true) => " ⏷",
+        Some(false) => " ⏵",
+        None => ""
+    };
+    let text = RichText::new(format!("{icon} {title}{arrow}"))
+        .strong()
+        .color(callout_color(kind));
+
+    match open {
+        Some(open) => {
+            let response = ui
+                .add(Label::new(text).sense(Sense::click()))
+                .on_hover_cursor(CursorIcon::PointingHand);
+            if response.clicked() {
+                *open ^= true;
+            }
+        }
+        None => {
+            ui.label(text);
+        }
+    }
+}
+
+pub fn item_ui(ui: &mut Ui, item: parser::Item<'_>) -> Option<Link> {
     let row_height = ui.text_style_height(&TextStyle::Body);
     let one_indent = row_height / 2.0;
 
@@ -44,7 +442,9 @@ pub fn item_ui(ui: &mut Ui, item: parser::Item<'_>) {
 
         parser::Item::Text(style, text) => {
             let label = rich_text_from_style(text, style);
-            if style.small && !style.raised {
+            if style.keyboard {
+                key_chip(ui, label);
+            } else if (style.small || style.lowered) && !style.raised {
                 ui.with_layout(Layout::left_to_right(Align::BOTTOM), |ui| {
                     ui.set_min_height(row_height);
                     ui.label(label);
@@ -54,15 +454,27 @@ pub fn item_ui(ui: &mut Ui, item: parser::Item<'_>) {
             }
         }
 
+        parser::Item::Hyperlink(style, text, url) if url.starts_with('#') => {
+            if link_label(ui, rich_text_from_style(text, style), url).clicked() {
+                return Some(Link::Fragment(url[1..].to_string()));
+            }
+        }
+
+        parser::Item::Hyperlink(style, text, url) if workspace::note_link(url).is_some() => {
+            if link_label(ui, rich_text_from_style(text, style), url).clicked() {
+                return Some(Link::File(url.to_string()));
+            }
+        }
+
         parser::Item::Hyperlink(style, text, url) => {
             let label = rich_text_from_style(text, style);
             if style.small && !style.raised {
                 ui.with_layout(Layout::left_to_right(Align::BOTTOM), |ui| {
                     ui.set_height(row_height);
-                    ui.add(Hyperlink::from_label_and_url(label, url));
+                    ui.add(Hyperlink::from_label_and_url(label, parser::link_target(url)));
                 });   
             } else {
-                ui.add(Hyperlink::from_label_and_url(label, url));
+                ui.add(Hyperlink::from_label_and_url(label, parser::link_target(url)));
             }
         }
 
@@ -87,13 +499,16 @@ pub fn item_ui(ui: &mut Ui, item: parser::Item<'_>) {
             );
         }
 
-        parser::Item::BulletPoint => {
-            ui.allocate_exact_size(vec2(one_indent, row_height), Sense::hover());
-            bullet_point(ui, one_indent);
+        parser::Item::BulletPoint(level) => {
+            let indent = (1 + 4 * level) as f32 * one_indent;
+            ui.allocate_exact_size(vec2(indent, row_height), Sense::hover());
+            bullet_point(ui, one_indent, level % 2 == 1);
             ui.allocate_exact_size(vec2(one_indent, row_height), Sense::hover());
         }
 
-        parser::Item::NumberedPoint(number) => {
+        parser::Item::NumberedPoint(level, number) => {
+            let indent = (4 * level) as f32 * one_indent;
+            ui.allocate_exact_size(vec2(indent, row_height), Sense::hover());
             let width = 3.0 * one_indent;
             numbered_point(ui, width, number);
             ui.allocate_exact_size(vec2(one_indent, row_height), Sense::hover());
@@ -102,7 +517,8 @@ pub fn item_ui(ui: &mut Ui, item: parser::Item<'_>) {
         parser::Item::CodeBlock(_language, code) => {
             let where_to_put_background = ui.painter().add(Shape::Noop);
             let mut rect = ui.monospace(code).rect;
-            rect = rect.expand(1.0);
+            // Close the gap between the lines of a code block in a quote.
+            rect = rect.expand2(vec2(1.0, ui.spacing().item_spacing.y * 0.5));
             rect.max.x = ui.max_rect().max.x;
             let code_bg_color = ui.visuals().code_bg_color;
             ui.painter().set(
@@ -111,12 +527,102 @@ pub fn item_ui(ui: &mut Ui, item: parser::Item<'_>) {
             );
         }
 
+        parser::Item::Callout(kind, _, title) => callout_title(ui, kind, title, None),
+
+        parser::Item::Emoji(style, emoji, shortcode) => {
+            // No font may have newer emoji, the shortcode reads better than
+            // boxes. Joiners and variation selectors draw nothing anyway.
+            let font_id = TextStyle::Body.resolve(ui.style());
+            let drawable = ui.fonts(|fonts| {
+                emoji
+                    .chars()
+                    .filter(|c| !matches!(c, '\u{200d}' | '\u{fe0e}' | '\u{fe0f}'))
+                    .all(|c| fonts.has_glyph(&font_id, c))
+            });
+            let shortcode = format!(":{shortcode}:");
+            let text = if drawable { emoji } else { shortcode.as_str() };
+            ui.label(rich_text_from_style(text, style)).on_hover_text(&shortcode);
+        }
+
+        parser::Item::Definition => {
+            ui.allocate_exact_size(vec2(4.0 * one_indent, row_height), Sense::hover());
+        }
+
+        parser::Item::Abbreviation(style, text, expansion) => {
+            let response = ui.label(rich_text_from_style(text, style)).on_hover_text(expansion);
+            // Dotted like an HTML <abbr>, so it does not look like a link.
+            let rect = response.rect;
+            let line = [rect.left_bottom(), rect.right_bottom()];
+            ui.painter().extend(Shape::dotted_line(&line, ui.visuals().weak_text_color(), 3.0, 0.5));
+        }
+
+        parser::Item::Custom(style, kind, text) => {
+            let extensions = Extensions::get(ui.ctx());
+            match extensions.renderers.get(kind) {
+                Some(render) => render(ui, style, text),
+                None => {
+                    ui.label(rich_text_from_style(text, style));
+                }
+            }
+        }
+
+        parser::Item::Math(tex, false) => {
+            math::inline(ui, tex);
+        }
+
+        parser::Item::Math(tex, true) => {
+            math::display(ui, tex);
+        }
+
         parser::Item::Todo(done) => {
             ui.allocate_exact_size(vec2(one_indent, row_height), Sense::hover());
-            todo(ui, done, one_indent);
+            todo(ui, done);
             ui.allocate_exact_size(vec2(one_indent, row_height), Sense::hover());
         }
+
+        parser::Item::Footnote(number, text) => {
+            let marker = RichText::new(number.to_string())
+                .small()
+                .raised()
+                .color(ui.visuals().hyperlink_color);
+            let response = ui
+                .add(Label::new(marker).sense(Sense::click()))
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .on_hover_ui(|ui| {
+                    ui.set_max_width(320.0);
+                    let options = ui.memory_mut(|memory| memory.data.get_temp(Id::new(OPTIONS)));
+                    let extensions = Extensions::get(ui.ctx());
+                    let footnote = parser::Parser::with_options(text, options.unwrap_or_default())
+                        .with_extensions(&extensions.parsers);
+                    mark_it(ui, footnote);
+                });
+            if response.clicked() {
+                ui.memory_mut(|memory| memory.data.insert_temp(Id::new(FOOTNOTE_TARGET), number));
+            }
+        }
+
+        parser::Item::WikiLink(style, target, label) => {
+            if link_label(ui, rich_text_from_style(label, style), target).clicked() {
+                return Some(Link::Wiki(target.to_string()));
+            }
+        }
+    }
+
+    None
+}
+
+/// Looks like a hyperlink, but is followed by the app rather than the
+/// browser.
+fn link_label(ui: &mut Ui, text: RichText, hover: &str) -> Response {
+    let color = ui.visuals().hyperlink_color;
+    let response = ui
+        .add(Label::new(text.color(color)).sense(Sense::click()))
+        .on_hover_cursor(CursorIcon::PointingHand)
+        .on_hover_text(hover);
+    if response.hovered() {
+        ui.painter().hline(response.rect.x_range(), response.rect.bottom(), (1.0, color));
     }
+    response
 }
 
 fn rich_text_from_style(text: &str, style: parser::Style) -> RichText {
@@ -130,9 +636,13 @@ fn rich_text_from_style(text: &str, style: parser::Style) -> RichText {
         strikethrough,
         italics,
         small,
-        raised
+        raised,
+        lowered,
+        highlight,
+        keyboard,
+        color
     } = style;
-    let small = small || raised;
+    let small = small || raised || lowered;
 
     let mut rich_text = RichText::new(text);
     if heading && !small {
@@ -164,22 +674,43 @@ fn rich_text_from_style(text: &str, style: parser::Style) -> RichText {
     if raised {
         rich_text = rich_text.raised();
     }
+    if keyboard {
+        rich_text = rich_text.monospace();
+    }
+    if highlight {
+        rich_text = rich_text.background_color(HIGHLIGHT);
+    }
+    if let Some([r, g, b]) = color {
+        rich_text = rich_text.color(Color32::from_rgb(r, g, b));
+    }
 
     rich_text
 }
 
-fn bullet_point(ui: &mut Ui, width: f32) -> Response {
+/// A key like `<kbd>Ctrl</kbd>`, framed like a key cap.
+fn key_chip(ui: &mut Ui, label: RichText) -> Response {
+    Frame::none()
+        .fill(ui.visuals().code_bg_color)
+        .stroke(Stroke::new(1.0, ui.visuals().weak_text_color()))
+        .rounding(Rounding::same(3.0))
+        .inner_margin(Margin::symmetric(3.0, 0.0))
+        .show(ui, |ui| ui.label(label))
+        .response
+}
+
+fn bullet_point(ui: &mut Ui, width: f32, hollow: bool) -> Response {
     let row_height = ui.text_style_height(&TextStyle::Body);
     let (rect, response) = ui.allocate_exact_size(
         vec2(width, row_height), 
         Sense::hover()
     );
 
-    ui.painter().circle_filled(
-        rect.center(),
-        rect.height() / 8.0,
-        ui.visuals().strong_text_color()
-    );
+    let color = ui.visuals().strong_text_color();
+    if hollow {
+        ui.painter().circle_stroke(rect.center(), rect.height() / 8.0, (1.0, color));
+    } else {
+        ui.painter().circle_filled(rect.center(), rect.height() / 8.0, color);
+    }
 
     response
 }
@@ -205,7 +736,7 @@ fn numbered_point(ui: &mut Ui, width: f32, number: &str) -> Response {
     response
 }
 
-fn todo(ui: &mut Ui, done: bool, indent: f32) -> Response {
+fn todo(ui: &mut Ui, done: bool) -> Response {
     let row_height = ui.text_style_height(&TextStyle::Body);
     let (rect, response) = ui.allocate_exact_size(
         vec2(14.0, row_height), 
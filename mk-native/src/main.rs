@@ -1,14 +1,17 @@
+use std::path::PathBuf;
 use mk_gui::{
     MarkoApp,
     eframe::{self, NativeOptions}
 };
 
 fn main() -> eframe::Result {
+    let path = std::env::args_os().nth(1).map(PathBuf::from);
+
     eframe::run_native(
         "Marko",
         NativeOptions::default(),
-        Box::new(|_| {
-            Ok(Box::new(MarkoApp::default()))
+        Box::new(|cc| {
+            Ok(Box::new(MarkoApp::new(cc, path)))
         })
     )
 }